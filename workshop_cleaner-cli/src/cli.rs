@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use workshop_cleaner_core::report::ReportFormat;
+
+/// Finds Steam Workshop items that are installed but not subscribed by the current Steam user.
+///
+/// Runs interactively when no subcommand is given.
+#[derive(Parser)]
+#[clap(version, about)]
+pub(crate) struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// List installed apps that have workshop content
+    Apps,
+    /// Show installed items, items no local account subscribes to and their size for all apps
+    Summary,
+    /// List items of the app that are installed but not subscribed
+    Scan {
+        /// App id, see `apps`
+        #[clap(long)]
+        app: u32,
+        #[clap(flatten)]
+        output: OutputArgs,
+    },
+    /// Remove items of the app that are installed but not subscribed
+    #[clap(group(ArgGroup::new("items").required(true).args(&["all", "ids"])))]
+    Clean {
+        /// App id, see `apps`
+        #[clap(long)]
+        app: u32,
+        /// Remove all items found by `scan`
+        #[clap(long)]
+        all: bool,
+        /// Remove only the listed items, they have to be found by `scan`
+        #[clap(long, multiple_values = true, value_delimiter = ',')]
+        ids: Vec<u64>,
+        /// Do not ask for confirmation
+        #[clap(long, short)]
+        yes: bool,
+        /// Only remove the files, do not send unsubscribe request to Steam
+        #[clap(long)]
+        no_unsubscribe: bool,
+        #[clap(flatten)]
+        output: OutputArgs,
+    },
+    /// Show disk space used by installed workshop items
+    Size {
+        /// Only show the app with given id
+        #[clap(long)]
+        app: Option<u32>,
+        /// Count blocks allocated on the disk instead of file lengths
+        #[clap(long)]
+        allocated: bool,
+    },
+    /// Show details of a workshop item
+    Info {
+        /// Workshop item id
+        item: u64,
+    },
+    /// Browse apps and remove items in a full-screen terminal UI
+    Tui {
+        /// Only remove the files, do not send unsubscribe request to Steam
+        #[clap(long)]
+        no_unsubscribe: bool,
+    },
+}
+
+#[derive(Args)]
+pub(crate) struct OutputArgs {
+    /// Output format, reports of `scan` list all installed items with their state
+    #[clap(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+    /// Write the report to a file instead of standard output
+    #[clap(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum OutputFormat {
+    Human,
+    Json,
+    Csv,
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// Report format for machine-readable output, `None` for human readable one.
+    pub fn report_format(self) -> Option<ReportFormat> {
+        match self {
+            OutputFormat::Human => None,
+            OutputFormat::Json => Some(ReportFormat::Json),
+            OutputFormat::Csv => Some(ReportFormat::Csv),
+            OutputFormat::Ndjson => Some(ReportFormat::Ndjson),
+        }
+    }
+}
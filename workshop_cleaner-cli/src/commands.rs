@@ -0,0 +1,379 @@
+use std::{fs::File, io::BufWriter, process::ExitCode};
+
+use workshop_cleaner_core::{
+    cleaner::RemovalStrategy,
+    disk_usage::{DiskUsageWalker, SizeMode},
+    locator::SteamLocator,
+    progress::Monitor,
+    report::{RemovalResult, Report, ReportFormat, ReportItem},
+    size_index::SizeIndex,
+    AppId, PublishedFileId,
+};
+use workshop_cleaner_session::{self as session, Event, Item, RemovalState, Session, SteamBackend};
+use workshop_cleaner_web::SteamWebClient;
+
+use crate::{
+    cli::{Command, OutputArgs},
+    io, tui,
+};
+
+// Exit codes, `2` is used by clap for invalid usage.
+pub(crate) const EXIT_ERROR: u8 = 1;
+/// Some of the items could not be removed.
+pub(crate) const EXIT_PARTIAL_FAILURE: u8 = 3;
+/// User did not confirm the removal.
+pub(crate) const EXIT_ABORTED: u8 = 4;
+
+/// Number of largest files listed by `info`.
+const INFO_LARGEST_FILES: usize = 5;
+
+type CommandResult = Result<ExitCode, String>;
+
+pub(crate) fn run(command: Command) -> ExitCode {
+    let web = SteamWebClient::new();
+
+    let result = match command {
+        Command::Apps => apps(&web),
+        Command::Summary => summary(&web),
+        Command::Scan { app, output } => scan(&web, AppId(app), &output),
+        Command::Clean {
+            app,
+            all,
+            ids,
+            yes,
+            no_unsubscribe,
+            output,
+        } => {
+            let ids = if all {
+                None
+            } else {
+                Some(ids.into_iter().map(PublishedFileId).collect())
+            };
+
+            clean(&web, AppId(app), ids, yes, !no_unsubscribe, &output)
+        }
+        Command::Size { app, allocated } => {
+            let mode = match allocated {
+                true => SizeMode::Allocated,
+                false => SizeMode::Apparent,
+            };
+            size(&web, app.map(AppId), mode)
+        }
+        Command::Info { item } => info(&web, PublishedFileId(item)),
+        Command::Tui { no_unsubscribe } => tui::run(!no_unsubscribe).map(|_| ExitCode::SUCCESS),
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+
+        ExitCode::from(EXIT_ERROR)
+    })
+}
+
+fn apps(web: &SteamWebClient) -> CommandResult {
+    for app in SteamLocator::locate()?.get_installed_workshop_apps() {
+        println!("{}", io::app_to_prompt_item(web, &app));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn summary(web: &SteamWebClient) -> CommandResult {
+    let backend = SteamBackend::new().with_web_client(web.clone());
+    let mut session = Session::new(backend, |_| ());
+    session.handle(session::Command::LocateApps { steam_root: None })?;
+
+    println!("{}", io::summary_table_header());
+    for app in session.apps() {
+        println!("{}", io::app_to_table_row(app));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn scan(web: &SteamWebClient, app_id: AppId, output: &OutputArgs) -> CommandResult {
+    let report_format = output.format.report_format();
+    let session = scan_session(web, app_id, report_format.is_some())?;
+
+    if let Some(format) = report_format {
+        let app_name = get_app_name(web, app_id);
+        let report_items = session
+            .items()
+            .iter()
+            .filter(|item| item.folder.is_none())
+            .map(|item| report_item(&session, app_id, &app_name, item))
+            .collect();
+        write_report(&Report::new(report_items), format, output)?;
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    for item in session.items().iter().filter(|i| i.is_removable()) {
+        println!("{}", io::item_to_prompt_item(item));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Remove items found by scan, all of them when `ids` are not given.
+fn clean(
+    web: &SteamWebClient,
+    app_id: AppId,
+    ids: Option<Vec<PublishedFileId>>,
+    yes: bool,
+    unsubscribe: bool,
+    output: &OutputArgs,
+) -> CommandResult {
+    let report_format = output.format.report_format();
+    let mut session = scan_session(web, app_id, report_format.is_some())?;
+    let found: Vec<&Item> = session
+        .items()
+        .iter()
+        .filter(|i| i.is_removable())
+        .collect();
+
+    let targets: Vec<u64> = match ids {
+        Some(ids) => ids
+            .iter()
+            .map(|id| {
+                found
+                    .iter()
+                    .find(|i| i.id == id.0)
+                    .map(|i| i.id)
+                    .ok_or_else(|| {
+                        format!(
+                            "Item {} is not installed or is subscribed by current user",
+                            id.0
+                        )
+                    })
+            })
+            .collect::<Result<_, _>>()?,
+        None => found.iter().map(|i| i.id).collect(),
+    };
+
+    if targets.is_empty() && report_format.is_none() {
+        println!("Hooray! No items found.");
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if report_format.is_none() {
+        for id in &targets {
+            if let Some(item) = session.items().iter().find(|i| i.id == *id) {
+                println!("{}", io::item_to_prompt_item(item));
+            }
+        }
+    }
+
+    if !targets.is_empty() && !yes && !confirm_removal(targets.len())? {
+        return Ok(ExitCode::from(EXIT_ABORTED));
+    }
+
+    session.handle(session::Command::Remove {
+        ids: targets.clone(),
+        unsubscribe,
+        strategy: RemovalStrategy::Delete,
+    })?;
+    let removed: Vec<&Item> = targets
+        .iter()
+        .filter_map(|id| session.items().iter().find(|i| i.id == *id))
+        .collect();
+
+    if let Some(format) = report_format {
+        let app_name = get_app_name(web, app_id);
+        let report_items: Vec<ReportItem> = removed
+            .iter()
+            .map(|item| {
+                let mut report_item = report_item(&session, app_id, &app_name, item);
+                report_item.removal = Some(match &item.removal {
+                    RemovalState::Failed(reason) => RemovalResult::Failed {
+                        reason: reason.clone(),
+                    },
+                    _ => RemovalResult::Removed,
+                });
+
+                report_item
+            })
+            .collect();
+        write_report(&Report::new(report_items), format, output)?;
+    }
+
+    match removed.iter().any(|i| i.removal != RemovalState::Removed) {
+        false => Ok(ExitCode::SUCCESS),
+        true => Ok(ExitCode::from(EXIT_PARTIAL_FAILURE)),
+    }
+}
+
+/// Session with the app scanned, its items have details and sizes.
+///
+/// Only warnings are printed when `quiet`, so stdout is left to the report. Items that could not
+/// be measured are kept with unknown size, only a failed scan is an error.
+fn scan_session(
+    web: &SteamWebClient,
+    app_id: AppId,
+    quiet: bool,
+) -> Result<Session<SteamBackend>, String> {
+    let backend = SteamBackend::new().with_web_client(web.clone());
+    let mut printer = io::event_printer();
+
+    let mut session = Session::new(backend, move |event| match &event {
+        // returned by `handle`, printed by `run`
+        Event::ScanFailed { .. } | Event::RemovalFailed(_) => (),
+        Event::DetailsFailed(_) | Event::SizesFailed(_) => printer(event),
+        _ if !quiet => printer(event),
+        _ => (),
+    });
+    session.handle(session::Command::SelectApp(app_id.0))?;
+
+    Ok(session)
+}
+
+fn get_app_name(web: &SteamWebClient, app_id: AppId) -> Option<String> {
+    let app = SteamLocator::locate()
+        .ok()?
+        .get_installed_workshop_apps()
+        .into_iter()
+        .find(|a| a.app_id == app_id)?;
+
+    Some(io::get_app_name(web, &app))
+}
+
+fn report_item(
+    session: &Session<SteamBackend>,
+    app_id: AppId,
+    app_name: &Option<String>,
+    item: &Item,
+) -> ReportItem {
+    ReportItem {
+        id: item.id,
+        title: item.title.clone(),
+        app_id: app_id.0,
+        app_name: app_name.clone(),
+        path: session
+            .workshop_dir()
+            .map(|dir| dir.join(item.dir_name()))
+            .unwrap_or_default(),
+        size_bytes: item.size,
+        state: item.state,
+        removal: None,
+    }
+}
+
+fn write_report(report: &Report, format: ReportFormat, output: &OutputArgs) -> Result<(), String> {
+    let result = match &output.output {
+        Some(path) => File::create(path).and_then(|f| report.write(BufWriter::new(f), format)),
+        None => report.write(std::io::stdout().lock(), format),
+    };
+
+    result.map_err(|e| format!("Could not write report ({})", e))
+}
+
+fn confirm_removal(count: usize) -> Result<bool, String> {
+    dialoguer::Confirm::with_theme(&io::theme())
+        .with_prompt(format!("Remove {} items?", count))
+        .default(false)
+        .interact()
+        .map_err(|e| {
+            format!(
+                "Could not ask for confirmation, use --yes to skip it ({})",
+                e
+            )
+        })
+}
+
+fn size(web: &SteamWebClient, app_id: Option<AppId>, mode: SizeMode) -> CommandResult {
+    let apps: Vec<_> = SteamLocator::locate()?
+        .get_installed_workshop_apps()
+        .into_iter()
+        .filter(|a| app_id.map_or(true, |id| id == a.app_id))
+        .collect();
+
+    if let (Some(app_id), true) = (app_id, apps.is_empty()) {
+        return Err(format!("App {} has no workshop content", app_id.0));
+    }
+
+    let walker = DiskUsageWalker::new().with_mode(mode);
+    let mut total = 0;
+    for app in &apps {
+        let items = app.get_installed_items();
+        let dirs: Vec<_> = items
+            .iter()
+            .map(|i| (i.0, app.workshop_dir().join(i.0.to_string())))
+            .collect();
+        let (size, files) = io::measure_dirs(&mut SizeIndex::open(app.app_id), &walker, &dirs)?
+            .into_iter()
+            .flatten()
+            .fold((0, 0), |(size, files), u| {
+                (size + u.bytes(mode), files + u.files)
+            });
+        total += size;
+
+        println!(
+            "{} - {} items - {} files - {}",
+            io::app_to_prompt_item(web, app),
+            items.len(),
+            files,
+            io::format_size(size)
+        );
+    }
+
+    if apps.len() > 1 {
+        println!("Total - {}", io::format_size(total));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn info(web: &SteamWebClient, item_id: PublishedFileId) -> CommandResult {
+    let details = web.get_published_file(item_id.0);
+    let local = SteamLocator::locate()?.find_workshop_item(item_id);
+
+    if let (Err(e), None) = (&details, &local) {
+        return Err(format!("Item {} not found ({})", item_id.0, e));
+    }
+
+    println!("ID: {}", item_id.0);
+    match &details {
+        Ok(d) => {
+            println!("Title: {}", d.title);
+            println!("App: {}", d.consumer_app_id);
+            println!("Author: {}", d.creator);
+            println!("Updated: {}", io::format_timestamp(d.time_updated));
+            println!("Size on Steam: {}", io::format_size(d.file_size));
+        }
+        Err(e) => println!("Details: not available ({})", e),
+    }
+    match &local {
+        Some((app, path)) => {
+            println!("Installed for: {}", io::app_to_prompt_item(web, app));
+            println!("Path: {}", path.display());
+            let walker = DiskUsageWalker::new().with_largest_files(INFO_LARGEST_FILES);
+            match walker
+                .measure_dir(path, &mut Monitor::none())
+                .ok()
+                .flatten()
+            {
+                Some(usage) => {
+                    println!("Size on disk: {}", io::format_size(usage.apparent_bytes));
+                    println!(
+                        "Allocated on disk: {}",
+                        io::format_size(usage.allocated_bytes)
+                    );
+                    println!("Files: {}", usage.files);
+                    println!("Largest files:");
+                    for entry in &usage.largest_files {
+                        println!(
+                            "  {} - {}",
+                            entry.path.display(),
+                            io::format_size(entry.size)
+                        );
+                    }
+                }
+                None => println!("Size on disk: unknown"),
+            }
+        }
+        None => println!("Installed: no"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
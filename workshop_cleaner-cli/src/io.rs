@@ -1,32 +1,169 @@
-use crate::model::WorkshopItem;
+use std::path::PathBuf;
+
+use chrono::DateTime;
 use dialoguer::theme;
 use humansize::FileSize;
-use workshop_cleaner_core::{cleaner::WorkshopCleaner, AppId};
+use indicatif::{ProgressBar, ProgressStyle};
+use workshop_cleaner_core::{
+    disk_usage::{DiskUsage, DiskUsageWalker},
+    locator::WorkshopApp,
+    progress::{CancelToken, Monitor, Progress},
+    size_index::SizeIndex,
+};
+use workshop_cleaner_session::{App, Event, Item, RemovalState, State};
+use workshop_cleaner_web::SteamWebClient;
+
+/// Measure directories of the items in one pass, `None` for the ones that could not be read.
+///
+/// Items unchanged since they were measured last time get their size from the index.
+pub(crate) fn measure_dirs(
+    index: &mut SizeIndex,
+    walker: &DiskUsageWalker,
+    dirs: &[(u64, PathBuf)],
+) -> Result<Vec<Option<DiskUsage>>, String> {
+    let bar = items_bar(dirs.len() as u64);
+    let mut monitor = Monitor::new(CancelToken::new(), |progress| show_measured(&bar, progress));
+
+    let usage = index.measure(walker, dirs, &mut monitor);
+    drop(monitor);
+    bar.finish_and_clear();
+
+    usage
+}
+
+/// Advance the bar once an item is measured, count of its files is shown meanwhile.
+fn show_measured(bar: &ProgressBar, progress: Progress) {
+    match progress {
+        Progress::BytesCounted { bytes, files, .. } => bar.set_message(files_message(files, bytes)),
+        Progress::ItemMeasured { .. } | Progress::ItemFailed { .. } => bar.inc(1),
+        _ => (),
+    }
+}
 
-pub(crate) fn workshop_to_prompt_item(cleaner: &WorkshopCleaner, item: &WorkshopItem) -> String {
-    let mut details = format!("{}", item);
+/// Bar of items measured so far, drawn on stderr only if it's a terminal.
+fn items_bar(len: u64) -> ProgressBar {
+    let bar = ProgressBar::new(len);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{prefix} [{bar:30}] {pos}/{len} {wide_msg}")
+            .progress_chars("=> "),
+    );
+    bar.set_prefix("Measuring");
 
-    details = match cleaner.get_item_size(&item.id) {
-        Some(size) => format!(
-            "{} - {}",
-            details,
-            size.file_size(humansize::file_size_opts::BINARY).unwrap()
-        ),
-        None => format!("{} - {}", details, "Size unknown"),
-    };
+    bar
+}
+
+/// Spinner of a job with unknown length, its message says what is done right now.
+fn spinner(prefix: &str) -> ProgressBar {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(ProgressStyle::default_spinner().template("{spinner} {prefix} {wide_msg}"));
+    spinner.set_prefix(prefix.to_string());
+    spinner.enable_steady_tick(100);
+
+    spinner
+}
 
-    details
+fn files_message(files: u64, bytes: u64) -> String {
+    format!("{} files ({})", files, format_size(bytes))
 }
 
-pub(crate) fn app_id_to_prompt_item(app_id: &AppId) -> String {
-    let mut details = format!("{}", app_id.0);
+pub(crate) fn format_size(size: u64) -> String {
+    size.file_size(humansize::file_size_opts::BINARY).unwrap()
+}
 
-    details = match crate::steam::get_app_item_details(app_id) {
-        Some(d) => format!("{} - {}", details, d.name),
-        None => format!("{} - {}", details, "Unknown name"),
-    };
+pub(crate) fn format_timestamp(timestamp: u64) -> String {
+    match DateTime::from_timestamp(timestamp as i64, 0) {
+        Some(t) => t.format("%Y-%m-%d %H:%M UTC").to_string(),
+        None => "unknown".to_string(),
+    }
+}
 
-    details
+pub(crate) fn item_to_prompt_item(item: &Item) -> String {
+    match item.size {
+        Some(size) => format!("{} - {}", item, format_size(size)),
+        None => format!("{} - {}", item, "Size unknown"),
+    }
+}
+
+pub(crate) fn app_to_prompt_item(web: &SteamWebClient, app: &WorkshopApp) -> String {
+    format!("{} - {}", app.app_id.0, get_app_name(web, app))
+}
+
+pub(crate) fn summary_table_header() -> String {
+    format!(
+        "{:<40} {:>9} {:>19} {:>11}",
+        "APP", "INSTALLED", "UNSUBSCRIBED BY ALL", "RECLAIMABLE"
+    )
+}
+
+pub(crate) fn app_to_table_row(app: &App) -> String {
+    format!(
+        "{:<40} {:>9} {:>19} {:>11}",
+        format!("{} - {}", app.app_id, app.name),
+        app.installed_items,
+        app.orphaned_items,
+        format_size(app.reclaimable_bytes)
+    )
+}
+
+/// Name of the app from local Steam files, falls back to the store API.
+pub(crate) fn get_app_name(web: &SteamWebClient, app: &WorkshopApp) -> String {
+    app.name
+        .clone()
+        .or_else(|| web.get_app_details(app.app_id.0).ok().map(|d| d.name))
+        .unwrap_or_else(|| "Unknown name".to_string())
+}
+
+/// Observer of the interactive session, prints its events in the same way as the subcommands do.
+pub(crate) fn event_printer() -> impl FnMut(Event) + Send {
+    // spinner of the scan or removal of an item, bar of the measured items
+    let mut bar: Option<ProgressBar> = None;
+
+    move |event| match event {
+        Event::ScanProgress { text, .. } => println!("{}", text),
+        Event::AppsFailed(e) | Event::ScanFailed { error: e, .. } | Event::RemovalFailed(e) => {
+            eprintln!("Error: {}", e)
+        }
+        Event::DetailsFailed(e) | Event::SizesFailed(e) => eprintln!("Warning: {}", e),
+        Event::ScanFinished { items, .. } => {
+            finish(&mut bar);
+            let workshop_items = items.iter().filter(|i| i.folder.is_none()).count();
+            bar = Some(items_bar(workshop_items as u64));
+        }
+        Event::ItemSize(..) => {
+            if let Some(bar) = &bar {
+                bar.inc(1);
+            }
+        }
+        Event::Progress(Progress::ItemStarted { id }) => bar
+            .get_or_insert_with(|| spinner("Reading state of item"))
+            .set_message(id.to_string()),
+        Event::Progress(Progress::BytesCounted { bytes, files, .. }) => {
+            if let Some(bar) = &bar {
+                bar.set_message(files_message(files, bytes));
+            }
+        }
+        Event::StateChanged(State::Ready) => finish(&mut bar),
+        Event::ItemRemoval(item) => match &item.removal {
+            RemovalState::Removing => bar = Some(spinner(&format!("Removing - {}", item))),
+            RemovalState::Removed => {
+                finish(&mut bar);
+                println!("Removing - {}: OK", item);
+            }
+            RemovalState::Failed(e) => {
+                finish(&mut bar);
+                println!("Removing - {}: ERR ({})", item, e);
+            }
+            RemovalState::Installed | RemovalState::Queued => (),
+        },
+        _ => (),
+    }
+}
+
+fn finish(bar: &mut Option<ProgressBar>) {
+    if let Some(bar) = bar.take() {
+        bar.finish_and_clear();
+    }
 }
 
 pub(crate) fn wait_for_keypress() {
@@ -1,43 +1,66 @@
-#[macro_use]
-extern crate lazy_static;
+use std::process::ExitCode;
 
-use model::WorkshopItem;
-use workshop_cleaner_core::{self, init, locator::SteamLocator};
+use clap::Parser;
+use workshop_cleaner_core::{self, cleaner::RemovalStrategy, helper};
+use workshop_cleaner_session::{Command, Session, SteamBackend};
 
+mod cli;
+mod commands;
 mod io;
-mod model;
-mod steam;
+mod tui;
 
-fn main() {
+fn main() -> ExitCode {
+    // Steam access of the selected app runs in a helper process started from this binary
+    if let Some(code) = helper::run_if_requested() {
+        std::process::exit(code);
+    }
+
+    match cli::Cli::parse().command {
+        Some(command) => commands::run(command),
+        None => {
+            interactive();
+
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Guided flow, used when no subcommand is given.
+fn interactive() {
     let theme = io::theme();
+    let mut session = Session::new(SteamBackend::new(), io::event_printer());
 
-    // App selection
+    // App selection, from the summary of all apps
+    println!("Scanning workshop content of all apps...");
+    if session
+        .handle(Command::LocateApps { steam_root: None })
+        .is_err()
+    {
+        return;
+    }
     let mut app_prompt = dialoguer::Select::with_theme(&theme);
-    let apps = SteamLocator::new().get_installed_workshop_apps();
-    for app in &apps {
-        app_prompt.item(io::app_id_to_prompt_item(app));
+    for app in session.apps() {
+        app_prompt.item(io::app_to_table_row(app));
     }
     let selected_app = app_prompt
-        .with_prompt("Please select app to check")
+        .with_prompt(format!(
+            "Please select app to check\n  {}",
+            io::summary_table_header()
+        ))
         .interact()
         .unwrap();
 
-    // Init cleaner with selected app
-    let cleaner = init(apps[selected_app]).unwrap();
-
-    println!("\n\n"); // add padding after steam init output
+    // Scan the selected app, titles and sizes of the items are loaded with it
+    let app_id = session.apps()[selected_app].app_id;
+    if session.handle(Command::SelectApp(app_id)).is_err() {
+        return;
+    }
+    println!();
 
-    // Get workshop items to clean and get thier detials from web api
-    let items: Vec<WorkshopItem> = cleaner
-        .get_installed_not_subscribed_items()
-        .into_iter()
-        .map(|id| WorkshopItem {
-            id,
-            title: match crate::steam::get_workshop_item_details(&id) {
-                Some(i) => i.title,
-                None => "Unknown".to_string(),
-            },
-        })
+    let items: Vec<_> = session
+        .items()
+        .iter()
+        .filter(|i| i.is_removable())
         .collect();
 
     if items.is_empty() {
@@ -48,7 +71,7 @@ fn main() {
     // Build the list of workshop items
     let mut prompt = dialoguer::MultiSelect::with_theme(&theme);
     for item in &items {
-        prompt.item(io::workshop_to_prompt_item(&cleaner, &item));
+        prompt.item(io::item_to_prompt_item(item));
     }
 
     println!("Below items are installed on your machine but are not subscribed by currently logged Steam user.");
@@ -60,16 +83,12 @@ fn main() {
         .unwrap();
 
     println!();
-    for selected in selections {
-        let item = &items[selected];
-
-        print!("Removing - {}", item);
-
-        match cleaner.remove_item(&item.id, true) {
-            Ok(_) => println!(": OK"),
-            Err(e) => println!(": ERR ({})", e),
-        }
-    }
+    let ids = selections.into_iter().map(|i| items[i].id).collect();
+    let _ = session.handle(Command::Remove {
+        ids,
+        unsubscribe: true,
+        strategy: RemovalStrategy::Delete,
+    });
     println!();
 
     io::wait_for_keypress();
@@ -1,5 +0,0 @@
-mod app_details;
-pub(crate) use app_details::AppDetails;
-
-mod workshop_item;
-pub(crate) use workshop_item::WorkshopItem;
@@ -0,0 +1,93 @@
+//! Full-screen terminal UI, runs the same session as the GUI.
+
+use std::{
+    io::{self, Stdout},
+    sync::mpsc,
+    time::Duration,
+};
+
+use crossterm::{
+    event::{self, Event as TermEvent, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use workshop_cleaner_session::{Command, Session, SteamBackend};
+
+use self::state::{Action, Tui};
+
+mod state;
+mod ui;
+
+/// How long to wait for a key before checking session events again.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Terminal in raw mode on the alternate screen, restored when dropped, also on errors.
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+
+        Ok(TerminalGuard {
+            terminal: Terminal::new(CrosstermBackend::new(stdout))?,
+        })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+/// Run the terminal UI until the user quits, removed items are unsubscribed if `unsubscribe`.
+pub(crate) fn run(unsubscribe: bool) -> Result<(), String> {
+    let (sender, events) = mpsc::channel();
+    let session = Session::new(SteamBackend::new(), move |event| {
+        let _ = sender.send(event);
+    })
+    .spawn();
+    session.send(Command::LocateApps { steam_root: None });
+
+    let mut guard = TerminalGuard::enter().map_err(|e| e.to_string())?;
+    let mut tui = Tui::new(unsubscribe);
+    let mut view = ui::View::default();
+
+    // a running removal is cancelled on quit, its end is still waited for
+    while !tui.quit || tui.is_busy() {
+        guard
+            .terminal
+            .draw(|f| ui::draw(f, &tui, &mut view))
+            .map_err(|e| e.to_string())?;
+
+        for event in events.try_iter() {
+            tui.handle_event(event);
+        }
+
+        if !event::poll(TICK).map_err(|e| e.to_string())? {
+            continue;
+        }
+        let key = match event::read().map_err(|e| e.to_string())? {
+            TermEvent::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        match tui.handle_key(key) {
+            Some(Action::Send(command)) => session.send(command),
+            Some(Action::Replace(command)) => {
+                session.cancel();
+                session.send(command);
+            }
+            Some(Action::Cancel) => session.cancel(),
+            None => (),
+        }
+    }
+
+    Ok(())
+}
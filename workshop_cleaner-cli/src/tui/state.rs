@@ -0,0 +1,597 @@
+use std::{cmp::Ordering, collections::HashSet, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use workshop_cleaner_core::{cleaner::RemovalStrategy, progress::Progress};
+use workshop_cleaner_session::{App, Command, Event, Item, RemovalState, RemovalSummary, State};
+
+use crate::io::format_size;
+
+/// Rows moved by Ctrl+d and Ctrl+u.
+const PAGE_ROWS: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Pane {
+    Apps,
+    Items,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Screen {
+    Browse,
+    /// Typing the item filter.
+    Filter,
+    /// Marked items are listed and removed once confirmed.
+    Confirm,
+    Help,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SortColumn {
+    Size,
+    Title,
+    State,
+    Updated,
+}
+
+impl SortColumn {
+    pub fn next(self) -> Self {
+        match self {
+            SortColumn::Size => SortColumn::Title,
+            SortColumn::Title => SortColumn::State,
+            SortColumn::State => SortColumn::Updated,
+            SortColumn::Updated => SortColumn::Size,
+        }
+    }
+
+    fn compare(self, a: &Item, b: &Item) -> Ordering {
+        match self {
+            SortColumn::Size => a.size.cmp(&b.size),
+            SortColumn::Title => a
+                .to_string()
+                .to_lowercase()
+                .cmp(&b.to_string().to_lowercase()),
+            SortColumn::State => state_label(a).cmp(state_label(b)),
+            SortColumn::Updated => a.time_updated.cmp(&b.time_updated),
+        }
+    }
+}
+
+/// What the terminal loop has to do with the session after a key.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Action {
+    Send(Command),
+    /// Cancel the running command, like loading of the previous app, and send another one.
+    Replace(Command),
+    Cancel,
+}
+
+/// Everything shown by the terminal UI, changed by keys and session events only.
+pub(crate) struct Tui {
+    pub apps: Vec<App>,
+    pub app_cursor: usize,
+    /// App whose items are shown.
+    pub app_id: Option<u32>,
+    pub items: Vec<Item>,
+    pub item_cursor: usize,
+    /// Items chosen for removal.
+    pub marked: HashSet<u64>,
+    pub workshop_dir: Option<PathBuf>,
+    pub sort: SortColumn,
+    pub descending: bool,
+    pub filter: String,
+    pub focus: Pane,
+    pub screen: Screen,
+    /// Last progress or result message, shown in the status bar.
+    pub status: String,
+    /// Finished and all items of the running removal.
+    pub removal: Option<(usize, usize)>,
+    /// State of the session, progress reports are described by it.
+    pub session: State,
+    pub quit: bool,
+    unsubscribe: bool,
+}
+
+impl Tui {
+    pub fn new(unsubscribe: bool) -> Self {
+        Tui {
+            apps: vec![],
+            app_cursor: 0,
+            app_id: None,
+            items: vec![],
+            item_cursor: 0,
+            marked: HashSet::new(),
+            workshop_dir: None,
+            sort: SortColumn::Size,
+            descending: true,
+            filter: String::new(),
+            focus: Pane::Apps,
+            screen: Screen::Browse,
+            status: "Scanning workshop content of all apps...".to_string(),
+            removal: None,
+            session: State::Start,
+            quit: false,
+            unsubscribe,
+        }
+    }
+
+    /// Items passing the filter, in the chosen order.
+    pub fn visible_items(&self) -> Vec<&Item> {
+        let filter = self.filter.to_lowercase();
+        let mut items: Vec<&Item> = self
+            .items
+            .iter()
+            .filter(|i| filter.is_empty() || i.to_string().to_lowercase().contains(&filter))
+            .collect();
+
+        items.sort_by(|a, b| match self.descending {
+            true => self.sort.compare(b, a),
+            false => self.sort.compare(a, b),
+        });
+
+        items
+    }
+
+    pub fn cursor_item(&self) -> Option<&Item> {
+        self.visible_items().get(self.item_cursor).copied()
+    }
+
+    pub fn marked_items(&self) -> Vec<&Item> {
+        self.visible_items()
+            .into_iter()
+            .filter(|i| self.is_marked(i))
+            .collect()
+    }
+
+    pub fn is_marked(&self, item: &Item) -> bool {
+        item.folder.is_none() && self.marked.contains(&item.id)
+    }
+
+    /// Bytes size of the marked items, unknown sizes are left out.
+    pub fn marked_size(&self) -> u64 {
+        self.marked_items().iter().filter_map(|i| i.size).sum()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.removal.is_some()
+    }
+
+    pub fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::AppsLocated(apps) => {
+                self.status = format!("Found {} apps with workshop content", apps.len());
+                self.apps = apps;
+                self.app_cursor = 0;
+            }
+            Event::AppsFailed(e) => self.status = format!("Could not find Steam: {}", e),
+            Event::ScanProgress { app_id, text } if Some(app_id) == self.app_id => {
+                self.status = text
+            }
+            Event::ScanFailed { app_id, error } if Some(app_id) == self.app_id => {
+                self.status = error
+            }
+            Event::ScanFinished {
+                app_id,
+                items,
+                workshop_dir,
+            } if Some(app_id) == self.app_id => {
+                self.status = format!("Found {} installed items, loading details...", items.len());
+                self.items = items;
+                self.workshop_dir = workshop_dir;
+                self.item_cursor = 0;
+                self.marked.clear();
+                self.focus = Pane::Items;
+            }
+            Event::ItemDetails(id, details) => {
+                if let Some(item) = self.item_mut(id) {
+                    item.set_details(&details);
+                }
+            }
+            Event::ItemSize(id, size) => {
+                if let Some(item) = self.item_mut(id) {
+                    item.size = size;
+                }
+            }
+            Event::DetailsFailed(e) | Event::SizesFailed(e) => self.status = e,
+            Event::ItemRemoval(removed) => {
+                let finished = matches!(
+                    removed.removal,
+                    RemovalState::Removed | RemovalState::Failed(_)
+                );
+                if let (true, Some((done, _))) = (finished, self.removal.as_mut()) {
+                    *done += 1;
+                }
+                if removed.removal == RemovalState::Removed {
+                    self.marked.remove(&removed.id);
+                }
+                if let Some(item) = self.item_mut(removed.id) {
+                    *item = removed;
+                }
+            }
+            Event::RemovalFailed(e) => {
+                self.removal = None;
+                self.status = format!("Could not remove items: {}", e);
+            }
+            Event::RemovalFinished(summary) => {
+                self.removal = None;
+                self.status = removal_message(&summary);
+            }
+            Event::StateChanged(state) => {
+                if self.session == State::LoadingDetails && state == State::Ready {
+                    self.status = format!("Found {} installed items", self.items.len());
+                }
+                self.session = state;
+            }
+            Event::Progress(progress) => self.show_progress(progress),
+            // of a previously selected app
+            Event::ScanProgress { .. } | Event::ScanFailed { .. } | Event::ScanFinished { .. } => {}
+        }
+    }
+
+    fn show_progress(&mut self, progress: Progress) {
+        match (self.session, progress) {
+            (State::Scanning, Progress::ItemStarted { id }) => {
+                self.status = format!("Reading state of item {}...", id)
+            }
+            (State::LoadingDetails, Progress::BytesCounted { id, bytes, files }) => {
+                self.status = format!(
+                    "Measuring item {}, {} files ({})...",
+                    id,
+                    files,
+                    format_size(bytes)
+                )
+            }
+            (State::Removing, Progress::BytesCounted { bytes, files, .. }) => {
+                if let Some((done, total)) = self.removal {
+                    self.status = format!(
+                        "Removing {} of {}, deleted {} files ({})...",
+                        done + 1,
+                        total,
+                        files,
+                        format_size(bytes)
+                    )
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return self.quit();
+        }
+
+        match self.screen {
+            Screen::Browse => self.browse_key(key),
+            Screen::Filter => {
+                self.filter_key(key);
+                None
+            }
+            Screen::Confirm => self.confirm_key(key),
+            Screen::Help => {
+                self.screen = Screen::Browse;
+                None
+            }
+        }
+    }
+
+    fn browse_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('q') => return self.quit(),
+            KeyCode::Esc if self.is_busy() => return Some(Action::Cancel),
+            KeyCode::Esc => self.filter.clear(),
+            KeyCode::Char('?') => self.screen = Screen::Help,
+            KeyCode::Char('j') | KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Char('d') if ctrl => self.move_cursor(PAGE_ROWS as isize),
+            KeyCode::Char('u') if ctrl => self.move_cursor(-(PAGE_ROWS as isize)),
+            KeyCode::Char('g') | KeyCode::Home => self.move_cursor(isize::MIN),
+            KeyCode::Char('G') | KeyCode::End => self.move_cursor(isize::MAX),
+            KeyCode::Char('h') | KeyCode::Left => self.focus = Pane::Apps,
+            KeyCode::Char('l') | KeyCode::Right if !self.items.is_empty() => {
+                self.focus = Pane::Items
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Pane::Apps if !self.items.is_empty() => Pane::Items,
+                    _ => Pane::Apps,
+                }
+            }
+            KeyCode::Enter if self.focus == Pane::Apps => return self.select_app(),
+            KeyCode::Char(' ') if self.focus == Pane::Items => self.toggle_mark(),
+            KeyCode::Char('a') if self.focus == Pane::Items => self.mark_all(),
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('S') => self.descending = !self.descending,
+            KeyCode::Char('/') => self.screen = Screen::Filter,
+            KeyCode::Char('x') | KeyCode::Delete if !self.is_busy() => self.request_removal(),
+            _ => (),
+        }
+
+        None
+    }
+
+    fn filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.screen = Screen::Browse,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.screen = Screen::Browse;
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => (),
+        }
+        self.item_cursor = 0;
+    }
+
+    fn confirm_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                self.screen = Screen::Browse;
+                let ids: Vec<u64> = self.marked_items().iter().map(|i| i.id).collect();
+                self.removal = Some((0, ids.len()));
+                self.status = format!("Removing {} items...", ids.len());
+
+                Some(Action::Send(Command::Remove {
+                    ids,
+                    unsubscribe: self.unsubscribe,
+                    strategy: RemovalStrategy::Delete,
+                }))
+            }
+            KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                self.screen = Screen::Browse;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn quit(&mut self) -> Option<Action> {
+        self.quit = true;
+        if !self.is_busy() {
+            return None;
+        }
+
+        // running removal stops before the next item
+        self.status = "Cancelling removal before quit...".to_string();
+        Some(Action::Cancel)
+    }
+
+    fn move_cursor(&mut self, rows: isize) {
+        let (cursor, len) = match self.focus {
+            Pane::Apps => (&mut self.app_cursor, self.apps.len()),
+            Pane::Items => {
+                let len = self.visible_items().len();
+                (&mut self.item_cursor, len)
+            }
+        };
+        let last = len.saturating_sub(1) as isize;
+
+        *cursor = (*cursor as isize).saturating_add(rows).max(0).min(last) as usize;
+    }
+
+    fn select_app(&mut self) -> Option<Action> {
+        let app_id = self.apps.get(self.app_cursor)?.app_id;
+        if self.is_busy() || self.app_id == Some(app_id) {
+            return None;
+        }
+
+        self.app_id = Some(app_id);
+        self.items.clear();
+        self.marked.clear();
+        self.workshop_dir = None;
+        self.item_cursor = 0;
+
+        Some(Action::Replace(Command::SelectApp(app_id)))
+    }
+
+    fn toggle_mark(&mut self) {
+        let (id, removable) = match self.cursor_item() {
+            Some(item) if item.folder.is_none() => (item.id, item.is_removable()),
+            _ => return,
+        };
+
+        if !self.marked.remove(&id) && removable {
+            self.marked.insert(id);
+        }
+        self.move_cursor(1);
+    }
+
+    /// Mark all removable items passing the filter, or unmark them if all are marked.
+    fn mark_all(&mut self) {
+        let ids: Vec<u64> = self
+            .visible_items()
+            .iter()
+            .filter(|i| i.is_removable())
+            .map(|i| i.id)
+            .collect();
+
+        if ids.iter().all(|id| self.marked.contains(id)) {
+            for id in ids {
+                self.marked.remove(&id);
+            }
+        } else {
+            self.marked.extend(ids);
+        }
+    }
+
+    /// Confirm removal of the marked items, the item under the cursor if none is marked.
+    fn request_removal(&mut self) {
+        if self.marked_items().is_empty() {
+            match self.cursor_item() {
+                Some(item) if item.is_removable() => {
+                    let id = item.id;
+                    self.marked.insert(id);
+                }
+                _ => return,
+            }
+        }
+
+        self.screen = Screen::Confirm;
+    }
+
+    fn item_mut(&mut self, id: u64) -> Option<&mut Item> {
+        self.items
+            .iter_mut()
+            .find(|i| i.id == id && i.folder.is_none())
+    }
+}
+
+/// State of the item for the table, removal in progress shown instead of the scanned one.
+pub(crate) fn state_label(item: &Item) -> &'static str {
+    match item.removal {
+        RemovalState::Queued => "queued",
+        RemovalState::Removing => "removing",
+        RemovalState::Removed => "removed",
+        RemovalState::Failed(_) => "failed",
+        RemovalState::Installed => item.state.as_str(),
+    }
+}
+
+fn removal_message(summary: &RemovalSummary) -> String {
+    match (summary.failed, summary.is_cancelled()) {
+        (0, false) => format!("Removed {} items", summary.removed),
+        (0, true) => format!(
+            "Removed {} of {} items, cancelled",
+            summary.removed, summary.total
+        ),
+        (failed, _) => format!(
+            "Removed {} of {} items, {} failed",
+            summary.removed, summary.total, failed
+        ),
+    }
+}
+
+/// Selected total of the status bar.
+pub(crate) fn selection_summary(tui: &Tui) -> String {
+    format!(
+        "{} selected, {}",
+        tui.marked_items().len(),
+        format_size(tui.marked_size())
+    )
+}
+
+#[cfg(test)]
+fn press(tui: &mut Tui, keys: &str) -> Option<Action> {
+    keys.chars()
+        .map(|c| {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+            tui.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        })
+        .last()
+        .flatten()
+}
+
+#[cfg(test)]
+fn scanned_tui() -> Tui {
+    use workshop_cleaner_core::cleaner::ItemState;
+
+    let mut tui = Tui::new(true);
+    tui.app_id = Some(107410);
+    let items = vec![
+        Item {
+            title: Some("Altis Life".to_string()),
+            size: Some(300),
+            ..Item::new(1, ItemState::Unsubscribed)
+        },
+        Item {
+            title: Some("CBA_A3".to_string()),
+            size: Some(200),
+            ..Item::new(2, ItemState::Pinned)
+        },
+        Item {
+            title: Some("Exile Mod".to_string()),
+            size: Some(100),
+            ..Item::new(3, ItemState::Unsubscribed)
+        },
+    ];
+    tui.handle_event(Event::ScanFinished {
+        app_id: 107410,
+        items,
+        workshop_dir: None,
+    });
+
+    tui
+}
+
+#[test]
+fn test_tui_sort_filter_and_mark() {
+    let mut tui = scanned_tui();
+    let ids = |tui: &Tui| -> Vec<u64> { tui.visible_items().iter().map(|i| i.id).collect() };
+    assert_eq!(tui.focus, Pane::Items);
+    assert_eq!(ids(&tui), [1, 2, 3]);
+
+    press(&mut tui, "sS");
+    assert_eq!(tui.sort, SortColumn::Title);
+    assert_eq!(ids(&tui), [1, 2, 3]);
+    press(&mut tui, "S");
+    assert_eq!(ids(&tui), [3, 2, 1]);
+
+    // pinned item can't be marked
+    press(&mut tui, "  ");
+    assert_eq!(tui.marked, [3].iter().copied().collect());
+    assert_eq!(selection_summary(&tui), "1 selected, 100 B");
+
+    press(&mut tui, "/alt\n");
+    assert_eq!(ids(&tui), [1]);
+    press(&mut tui, "a");
+    assert_eq!(tui.marked, [1, 3].iter().copied().collect());
+    // hidden items are not removed
+    assert_eq!(tui.marked_items().len(), 1);
+}
+
+#[test]
+fn test_tui_confirm_removal() {
+    let mut tui = scanned_tui();
+
+    assert_eq!(press(&mut tui, "x"), None);
+    assert_eq!(tui.screen, Screen::Confirm);
+    assert_eq!(press(&mut tui, "n"), None);
+    assert_eq!(tui.screen, Screen::Browse);
+
+    assert_eq!(
+        press(&mut tui, "xy"),
+        Some(Action::Send(Command::Remove {
+            ids: vec![1],
+            unsubscribe: true,
+            strategy: RemovalStrategy::Delete,
+        }))
+    );
+    assert!(tui.is_busy());
+    assert_eq!(press(&mut tui, "q"), Some(Action::Cancel));
+
+    let mut removed = tui.items[0].clone();
+    removed.removal = RemovalState::Removed;
+    tui.handle_event(Event::ItemRemoval(removed));
+    assert_eq!(tui.removal, Some((1, 1)));
+    assert!(tui.marked.is_empty());
+}
+
+#[test]
+fn test_tui_select_app() {
+    let mut tui = Tui::new(true);
+    let app = |app_id| App {
+        app_id,
+        name: "Arma 3".to_string(),
+        installed_items: 3,
+        orphaned_items: 1,
+        reclaimable_bytes: 100,
+    };
+    tui.handle_event(Event::AppsLocated(vec![app(107410), app(255710)]));
+
+    assert_eq!(
+        press(&mut tui, "j\n"),
+        Some(Action::Replace(Command::SelectApp(255710)))
+    );
+    assert_eq!(press(&mut tui, "\n"), None);
+    assert_eq!(
+        press(&mut tui, "k\n"),
+        Some(Action::Replace(Command::SelectApp(107410)))
+    );
+}
@@ -0,0 +1,314 @@
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState,
+        Wrap,
+    },
+    Frame,
+};
+use workshop_cleaner_session::RemovalState;
+
+use super::state::{selection_summary, state_label, Pane, Screen, SortColumn, Tui};
+use crate::io::{format_size, format_timestamp};
+
+const HELP: &[(&str, &str)] = &[
+    ("j/k, arrows", "move down/up"),
+    ("g/G", "jump to top/bottom"),
+    ("Ctrl+d/Ctrl+u", "move by 10 rows"),
+    ("h/l, Tab", "switch between apps and items"),
+    ("Enter", "open the app"),
+    ("space", "mark item for removal"),
+    ("a", "mark all shown items"),
+    ("s/S", "change sort column/order"),
+    ("/", "filter items, Esc clears"),
+    ("x, Delete", "remove marked items"),
+    ("Esc", "cancel running removal"),
+    ("q", "quit"),
+];
+
+/// Scroll positions of the lists, kept between frames.
+#[derive(Default)]
+pub(crate) struct View {
+    apps: ListState,
+    items: TableState,
+}
+
+pub(crate) fn draw<B: Backend>(f: &mut Frame<B>, tui: &Tui, view: &mut View) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(f.size());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+        ])
+        .split(rows[0]);
+
+    draw_apps(f, tui, view, panes[0]);
+    draw_items(f, tui, view, panes[1]);
+    draw_details(f, tui, panes[2]);
+    draw_status(f, tui, rows[1]);
+
+    match tui.screen {
+        Screen::Confirm => draw_confirm(f, tui),
+        Screen::Help => draw_help(f),
+        Screen::Browse | Screen::Filter => (),
+    }
+}
+
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let style = match focused {
+        true => Style::default().fg(Color::Cyan),
+        false => Style::default(),
+    };
+
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+fn highlight() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+fn draw_apps<B: Backend>(f: &mut Frame<B>, tui: &Tui, view: &mut View, area: Rect) {
+    let items: Vec<ListItem> = tui
+        .apps
+        .iter()
+        .map(|app| {
+            let style = match Some(app.app_id) == tui.app_id {
+                true => Style::default().add_modifier(Modifier::BOLD),
+                false => Style::default(),
+            };
+            ListItem::new(Spans::from(vec![
+                Span::styled(app.name.clone(), style),
+                Span::styled(
+                    format!(" {}", app.orphaned_items),
+                    Style::default().fg(Color::Yellow),
+                ),
+            ]))
+        })
+        .collect();
+
+    view.apps
+        .select(Some(tui.app_cursor).filter(|_| !tui.apps.is_empty()));
+    let list = List::new(items)
+        .block(pane_block("Apps".to_string(), tui.focus == Pane::Apps))
+        .highlight_style(highlight());
+    f.render_stateful_widget(list, area, &mut view.apps);
+}
+
+fn draw_items<B: Backend>(f: &mut Frame<B>, tui: &Tui, view: &mut View, area: Rect) {
+    let items = tui.visible_items();
+    let header_cell = |title: &'static str, column: SortColumn| {
+        let arrow = match (tui.sort == column, tui.descending) {
+            (false, _) => "",
+            (true, true) => " v",
+            (true, false) => " ^",
+        };
+        Cell::from(format!("{}{}", title, arrow))
+    };
+    let header = Row::new(vec![
+        Cell::from(""),
+        header_cell("Title", SortColumn::Title),
+        header_cell("State", SortColumn::State),
+        header_cell("Size", SortColumn::Size),
+        header_cell("Updated", SortColumn::Updated),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = items.iter().map(|item| {
+        let style = match item.removal {
+            RemovalState::Removed => Style::default().fg(Color::DarkGray),
+            RemovalState::Failed(_) => Style::default().fg(Color::Red),
+            _ if !item.is_removable() => Style::default().fg(Color::DarkGray),
+            _ => Style::default(),
+        };
+        Row::new(vec![
+            Cell::from(if tui.is_marked(item) { "[x]" } else { "[ ]" }),
+            Cell::from(item.to_string()),
+            Cell::from(state_label(item)),
+            Cell::from(item.size.map(format_size).unwrap_or_default()),
+            Cell::from(
+                item.time_updated
+                    .map(|t| format_timestamp(t).chars().take(10).collect::<String>())
+                    .unwrap_or_default(),
+            ),
+        ])
+        .style(style)
+    });
+
+    let title = match (tui.screen, tui.filter.is_empty()) {
+        (Screen::Filter, _) => format!("Items /{}_", tui.filter),
+        (_, false) => format!("Items /{}", tui.filter),
+        (_, true) => "Items".to_string(),
+    };
+    view.items
+        .select(Some(tui.item_cursor).filter(|_| !items.is_empty()));
+    let table = Table::new(rows)
+        .header(header)
+        .block(pane_block(title, tui.focus == Pane::Items))
+        .highlight_style(highlight())
+        .widths(&[
+            Constraint::Length(3),
+            Constraint::Percentage(50),
+            Constraint::Length(14),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ]);
+    f.render_stateful_widget(table, area, &mut view.items);
+}
+
+fn draw_details<B: Backend>(f: &mut Frame<B>, tui: &Tui, area: Rect) {
+    let block = pane_block("Details".to_string(), false);
+    let item = match tui.cursor_item() {
+        Some(item) if tui.focus == Pane::Items => item,
+        _ => {
+            f.render_widget(block, area);
+            return;
+        }
+    };
+
+    let field = |name: &str, value: String| {
+        Spans::from(vec![
+            Span::styled(
+                format!("{}: ", name),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(value),
+        ])
+    };
+    let unknown = || "unknown".to_string();
+    let mut lines = vec![
+        field(
+            "Title",
+            item.title
+                .clone()
+                .or_else(|| item.folder.clone())
+                .unwrap_or_else(unknown),
+        ),
+        field("Author", item.author.clone().unwrap_or_else(unknown)),
+        field("State", state_label(item).to_string()),
+        field("Size", item.size.map(format_size).unwrap_or_else(unknown)),
+        field(
+            "Updated",
+            item.time_updated
+                .map(format_timestamp)
+                .unwrap_or_else(unknown),
+        ),
+    ];
+    if item.folder.is_none() {
+        lines.insert(1, field("ID", item.id.to_string()));
+    }
+    if let Some(dir) = &tui.workshop_dir {
+        lines.push(field(
+            "Path",
+            dir.join(item.dir_name()).display().to_string(),
+        ));
+    }
+    if let RemovalState::Failed(e) = &item.removal {
+        lines.push(Spans::from(Span::styled(
+            format!("Removal failed: {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    if let Some(description) = &item.description {
+        lines.push(Spans::default());
+        lines.extend(description.lines().map(|l| Spans::from(l.to_string())));
+    }
+
+    let details = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, area);
+}
+
+fn draw_status<B: Backend>(f: &mut Frame<B>, tui: &Tui, area: Rect) {
+    let progress = match tui.removal {
+        Some((done, total)) => format!(" [{}/{}, Esc to cancel]", done, total),
+        None => String::new(),
+    };
+    let right = format!("{} | ? help", selection_summary(tui));
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(right.len() as u16)])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(format!("{}{}", tui.status, progress)),
+        columns[0],
+    );
+    f.render_widget(
+        Paragraph::new(right).alignment(Alignment::Right),
+        columns[1],
+    );
+}
+
+fn draw_confirm<B: Backend>(f: &mut Frame<B>, tui: &Tui) {
+    let items = tui.marked_items();
+    let mut lines = vec![
+        Spans::from(format!(
+            "Remove {} items ({}) from the disk?",
+            items.len(),
+            format_size(tui.marked_size())
+        )),
+        Spans::default(),
+    ];
+    lines.extend(items.iter().map(|i| Spans::from(format!("  {}", i))));
+    lines.push(Spans::default());
+    lines.push(Spans::from(Span::styled(
+        "y/Enter to remove, n/Esc to go back",
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+
+    let area = centered(f.size(), 60, lines.len() as u16 + 2);
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Confirm removal"),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+fn draw_help<B: Backend>(f: &mut Frame<B>) {
+    let lines: Vec<Spans> = HELP
+        .iter()
+        .map(|(keys, action)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:>15}  ", keys),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(*action),
+            ])
+        })
+        .collect();
+
+    let area = centered(f.size(), 50, lines.len() as u16 + 2);
+    let popup = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Keys"));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+/// Area of given width percentage and height in rows, in the middle of `area`.
+fn centered(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
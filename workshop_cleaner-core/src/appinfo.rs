@@ -0,0 +1,361 @@
+//! Reader for Steam's binary app info cache, `appcache/appinfo.vdf`.
+//!
+//! The cache contains details of every app the Steam client has seen, including uninstalled ones.
+
+use std::{collections::HashMap, convert::TryInto, path::Path};
+
+use crate::{vdf::Vdf, AppId};
+
+const MAGIC_V27: u32 = 0x0756_4427;
+const MAGIC_V28: u32 = 0x0756_4428;
+const MAGIC_V29: u32 = 0x0756_4429;
+
+/// Info state, last updated, PICS token, text SHA-1 and change number.
+const ENTRY_HEADER_SIZE: usize = 4 + 4 + 8 + 20 + 4;
+/// Binary SHA-1, only in V28 and newer.
+const ENTRY_BINARY_SHA_SIZE: usize = 20;
+
+const TYPE_TABLE: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT32: u8 = 0x02;
+const TYPE_FLOAT32: u8 = 0x03;
+const TYPE_POINTER: u8 = 0x04;
+const TYPE_WIDE_STRING: u8 = 0x05;
+const TYPE_COLOR: u8 = 0x06;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+const TYPE_INT64: u8 = 0x0A;
+const TYPE_END_ALT: u8 = 0x0B;
+
+/// Deepest nesting of tables accepted, app info is nested only a few levels deep.
+const MAX_DEPTH: usize = 64;
+
+/// Read app info of requested apps, apps missing from the cache are left out.
+///
+/// Numbers are converted to strings so the result has the same shape as text KeyValues.
+pub fn read(path: &Path, app_ids: &[AppId]) -> Result<HashMap<u32, Vdf>, String> {
+    let data =
+        std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+    parse(&data, app_ids)
+}
+
+/// Read names of requested apps from the app info cache.
+pub fn read_names(path: &Path, app_ids: &[AppId]) -> Result<HashMap<u32, String>, String> {
+    Ok(read(path, app_ids)?
+        .into_iter()
+        .filter_map(|(app_id, info)| {
+            let name = info.get_path(&["appinfo", "common", "name"])?.as_str()?;
+
+            Some((app_id, name.to_string()))
+        })
+        .collect())
+}
+
+pub fn parse(data: &[u8], app_ids: &[AppId]) -> Result<HashMap<u32, Vdf>, String> {
+    let mut reader = Reader::new(data);
+
+    let magic = reader.u32()?;
+    let _universe = reader.u32()?;
+    let entry_header_size = match magic {
+        MAGIC_V27 => ENTRY_HEADER_SIZE,
+        MAGIC_V28 | MAGIC_V29 => ENTRY_HEADER_SIZE + ENTRY_BINARY_SHA_SIZE,
+        _ => return Err(format!("Unsupported appinfo.vdf version {:#x}", magic)),
+    };
+    let string_table = if magic == MAGIC_V29 {
+        let offset = reader.u64()? as usize;
+        Some(read_string_table(
+            data.get(offset..).ok_or("Invalid string table offset")?,
+        )?)
+    } else {
+        None
+    };
+
+    let mut apps = HashMap::new();
+    loop {
+        let app_id = reader.u32()?;
+        if app_id == 0 {
+            break;
+        }
+
+        let size = reader.u32()? as usize;
+        let entry = reader.bytes(size)?;
+
+        if !app_ids.iter().any(|a| a.0 == app_id) {
+            continue;
+        }
+
+        let mut entry_reader = Reader::new(entry);
+        entry_reader.bytes(entry_header_size)?;
+        let info = read_table(&mut entry_reader, string_table.as_deref(), 0)?;
+
+        apps.insert(app_id, info);
+    }
+
+    Ok(apps)
+}
+
+fn read_string_table(data: &[u8]) -> Result<Vec<String>, String> {
+    let mut reader = Reader::new(data);
+
+    let count = reader.u32()?;
+    (0..count).map(|_| reader.cstr()).collect()
+}
+
+/// Read entries of a table nested in `depth` other tables, until its end.
+fn read_table(
+    reader: &mut Reader,
+    string_table: Option<&[String]>,
+    depth: usize,
+) -> Result<Vdf, String> {
+    if depth > MAX_DEPTH {
+        return Err("Tables of appinfo.vdf are nested too deep".to_string());
+    }
+    let mut entries = vec![];
+
+    loop {
+        let value_type = reader.u8()?;
+        if value_type == TYPE_END || value_type == TYPE_END_ALT {
+            return Ok(Vdf::Table(entries));
+        }
+
+        let key = match string_table {
+            Some(strings) => {
+                let index = reader.u32()? as usize;
+                strings
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| format!("Invalid string table index {}", index))?
+            }
+            None => reader.cstr()?,
+        };
+
+        let value = match value_type {
+            TYPE_TABLE => read_table(reader, string_table, depth + 1)?,
+            TYPE_STRING => Vdf::Value(reader.cstr()?),
+            TYPE_INT32 | TYPE_POINTER | TYPE_COLOR => Vdf::Value(reader.i32()?.to_string()),
+            TYPE_FLOAT32 => Vdf::Value(f32::from_bits(reader.u32()?).to_string()),
+            TYPE_UINT64 => Vdf::Value(reader.u64()?.to_string()),
+            TYPE_INT64 => Vdf::Value((reader.u64()? as i64).to_string()),
+            TYPE_WIDE_STRING => Vdf::Value(reader.wstr()?),
+            t => return Err(format!("Unknown value type {:#x} of key {}", t, key)),
+        };
+
+        entries.push((key, value));
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or("Unexpected end of appinfo.vdf")?;
+        self.pos += len;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Null terminated UTF-8 string.
+    fn cstr(&mut self) -> Result<String, String> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or("Unterminated string in appinfo.vdf")?;
+        let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+
+        Ok(s)
+    }
+
+    /// Null terminated UTF-16 string.
+    fn wstr(&mut self) -> Result<String, String> {
+        let mut units = vec![];
+        loop {
+            let unit = u16::from_le_bytes(self.bytes(2)?.try_into().unwrap());
+            if unit == 0 {
+                return Ok(String::from_utf16_lossy(&units));
+            }
+            units.push(unit);
+        }
+    }
+}
+
+#[cfg(test)]
+fn build_test_appinfo(magic: u32, apps: &[(u32, &str)]) -> Vec<u8> {
+    let v29 = magic == MAGIC_V29;
+    let strings = ["appinfo", "common", "name", "type"];
+    let key = |buf: &mut Vec<u8>, key: &str| {
+        if v29 {
+            let index = strings.iter().position(|s| *s == key).unwrap() as u32;
+            buf.extend(index.to_le_bytes());
+        } else {
+            buf.extend(key.as_bytes());
+            buf.push(0);
+        }
+    };
+
+    let mut data = vec![];
+    data.extend(magic.to_le_bytes());
+    data.extend(1u32.to_le_bytes());
+    let string_table_offset_pos = data.len();
+    if v29 {
+        data.extend(0u64.to_le_bytes());
+    }
+
+    for (app_id, name) in apps {
+        let mut entry = vec![0u8; ENTRY_HEADER_SIZE];
+        if magic != MAGIC_V27 {
+            entry.extend([0u8; ENTRY_BINARY_SHA_SIZE]);
+        }
+        entry.push(TYPE_TABLE);
+        key(&mut entry, "appinfo");
+        entry.push(TYPE_TABLE);
+        key(&mut entry, "common");
+        entry.push(TYPE_STRING);
+        key(&mut entry, "name");
+        entry.extend(name.as_bytes());
+        entry.push(0);
+        entry.push(TYPE_INT32);
+        key(&mut entry, "type");
+        entry.extend(1i32.to_le_bytes());
+        entry.extend([TYPE_END, TYPE_END, TYPE_END]);
+
+        data.extend(app_id.to_le_bytes());
+        data.extend((entry.len() as u32).to_le_bytes());
+        data.extend(entry);
+    }
+    data.extend(0u32.to_le_bytes());
+
+    if v29 {
+        let offset = data.len() as u64;
+        data[string_table_offset_pos..string_table_offset_pos + 8]
+            .copy_from_slice(&offset.to_le_bytes());
+        data.extend((strings.len() as u32).to_le_bytes());
+        for s in strings.iter() {
+            data.extend(s.as_bytes());
+            data.push(0);
+        }
+    }
+
+    data
+}
+
+/// App info of app `4000` with the entry, after its header.
+#[cfg(test)]
+fn build_test_entry(body: &[u8]) -> Vec<u8> {
+    let mut entry = vec![0u8; ENTRY_HEADER_SIZE];
+    entry.extend(body);
+
+    let mut data = vec![];
+    data.extend(MAGIC_V27.to_le_bytes());
+    data.extend(1u32.to_le_bytes());
+    data.extend(4000u32.to_le_bytes());
+    data.extend((entry.len() as u32).to_le_bytes());
+    data.extend(entry);
+    data.extend(0u32.to_le_bytes());
+
+    data
+}
+
+#[test]
+fn test_parse_names() {
+    for magic in [MAGIC_V27, MAGIC_V28, MAGIC_V29] {
+        let data = build_test_appinfo(magic, &[(107410, "Arma 3"), (4000, "Garry's Mod")]);
+
+        let apps = parse(&data, &[AppId(4000)]).unwrap();
+
+        assert_eq!(apps.len(), 1);
+        assert_eq!(
+            apps[&4000]
+                .get_path(&["appinfo", "common", "name"])
+                .and_then(Vdf::as_str),
+            Some("Garry's Mod")
+        );
+        assert_eq!(
+            apps[&4000]
+                .get_path(&["appinfo", "common", "type"])
+                .and_then(Vdf::as_str),
+            Some("1")
+        );
+    }
+}
+
+#[test]
+fn test_parse_truncated() {
+    for magic in [MAGIC_V27, MAGIC_V28, MAGIC_V29] {
+        let data = build_test_appinfo(magic, &[(107410, "Arma 3"), (4000, "Garry's Mod")]);
+
+        for len in 0..data.len() {
+            assert!(parse(&data[..len], &[AppId(4000)]).is_err(), "{}", len);
+        }
+    }
+}
+
+#[test]
+fn test_parse_invalid_values() {
+    let mut body = vec![TYPE_TABLE];
+    body.extend(b"appinfo\0");
+    body.push(0x09);
+    body.extend(b"name\0");
+    body.extend([TYPE_END, TYPE_END]);
+    assert_eq!(
+        parse(&build_test_entry(&body), &[AppId(4000)]),
+        Err("Unknown value type 0x9 of key name".to_string())
+    );
+
+    // key "name" of the V29 app points past the string table
+    let mut data = build_test_appinfo(MAGIC_V29, &[(4000, "Garry's Mod")]);
+    let name_key = [TYPE_STRING, 2, 0, 0, 0];
+    let pos = data.windows(5).position(|w| w == name_key).unwrap();
+    data[pos + 1] = 99;
+    assert_eq!(
+        parse(&data, &[AppId(4000)]),
+        Err("Invalid string table index 99".to_string())
+    );
+}
+
+#[test]
+fn test_parse_nesting_limit() {
+    let nested = |depth: usize| {
+        let mut body = vec![];
+        for _ in 0..depth {
+            body.extend([TYPE_TABLE, b'a', 0]);
+        }
+        body.extend(vec![TYPE_END; depth + 1]);
+
+        parse(&build_test_entry(&body), &[AppId(4000)])
+    };
+
+    assert!(nested(MAX_DEPTH).is_ok());
+    assert_eq!(
+        nested(100_000),
+        Err("Tables of appinfo.vdf are nested too deep".to_string())
+    );
+}
@@ -1,12 +1,57 @@
-use std::{path::PathBuf, sync::mpsc::channel};
+use std::{
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Mutex},
+};
 
+use serde::{Deserialize, Serialize};
 use steamworks::PublishedFileId;
 
-use crate::ToPublishedFileId;
+use crate::{
+    disk_usage::{DiskUsage, DiskUsageWalker},
+    progress::{Monitor, Progress},
+    size_index::SizeIndex,
+    ToPublishedFileId,
+};
+
+/// What happens to the directory of a removed workshop item.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalStrategy {
+    /// Directory is deleted right away.
+    Delete,
+    /// Directory is moved into another directory, to be restored or deleted later.
+    MoveTo(PathBuf),
+}
+
+/// State of an installed workshop item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemState {
+    /// Installed but not subscribed by current user, Steam "forgot" to remove it.
+    Unsubscribed,
+    /// Subscribed by current user, Steam keeps it installed.
+    Pinned,
+    /// Steam is downloading or updating the item.
+    InUse,
+    /// Directory in the workshop content directory that is not named by an item ID.
+    UnknownFolder,
+}
+
+impl ItemState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemState::Unsubscribed => "unsubscribed",
+            ItemState::Pinned => "pinned",
+            ItemState::InUse => "in_use",
+            ItemState::UnknownFolder => "unknown_folder",
+        }
+    }
+}
 
 pub struct WorkshopCleaner {
     app_id: steamworks::AppId,
     client: steamworks::Client,
+    size_index: Mutex<SizeIndex>,
 }
 
 impl WorkshopCleaner {
@@ -18,7 +63,15 @@ impl WorkshopCleaner {
             return Err(format!("Selected app {} is not installed", app_id.0));
         }
 
-        Ok(WorkshopCleaner { app_id, client })
+        Ok(WorkshopCleaner {
+            app_id,
+            client,
+            size_index: Mutex::new(SizeIndex::open(app_id)),
+        })
+    }
+
+    pub fn app_id(&self) -> steamworks::AppId {
+        self.app_id
     }
 
     pub fn client(&self) -> &steamworks::Client {
@@ -42,20 +95,44 @@ impl WorkshopCleaner {
     }
 
     pub fn get_installed_items(&self) -> Vec<steamworks::PublishedFileId> {
-        let workshop_dir = self.get_workshop_dir();
+        read_installed_items(&self.get_workshop_dir())
+    }
 
-        // get all subdirectories in steam workshop dir
-        let directories: Vec<PathBuf> = std::fs::read_dir(workshop_dir.as_os_str())
-            .unwrap()
-            .into_iter()
-            .filter(|r| r.is_ok())
-            .map(|r| r.unwrap().path())
-            .filter(|p| p.is_dir())
-            .collect();
+    /// Get directories in the workshop content directory that are not workshop items.
+    pub fn get_unknown_folders(&self) -> Vec<PathBuf> {
+        read_unknown_folders(&self.get_workshop_dir())
+    }
+
+    /// Get all installed workshop items together with their state.
+    pub fn get_installed_items_with_state(&self) -> Vec<(PublishedFileId, ItemState)> {
+        self.get_installed_items_monitored(&mut Monitor::none())
+            .unwrap_or_default()
+    }
 
-        directories
+    /// Same as `get_installed_items_with_state`, each item is reported as started.
+    pub fn get_installed_items_monitored(
+        &self,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<(PublishedFileId, ItemState)>, String> {
+        let items_subscribed = self.client.ugc().subscribed_items();
+        let busy = steamworks::ItemState::DOWNLOADING | steamworks::ItemState::DOWNLOAD_PENDING;
+
+        self.get_installed_items()
             .into_iter()
-            .filter_map(|p| p.to_published_file_id())
+            .map(|id| {
+                monitor.check()?;
+                monitor.report(Progress::ItemStarted { id: id.0 });
+
+                let state = if self.client.ugc().item_state(id).intersects(busy) {
+                    ItemState::InUse
+                } else if items_subscribed.contains(&id) {
+                    ItemState::Pinned
+                } else {
+                    ItemState::Unsubscribed
+                };
+
+                Ok((id, state))
+            })
             .collect()
     }
 
@@ -70,27 +147,115 @@ impl WorkshopCleaner {
             .collect()
     }
 
+    /// Get persona name of a Steam user, `None` if Steam does not know it yet.
+    ///
+    /// Unknown users are requested from Steam, so a later call can succeed.
+    pub fn get_user_name(&self, steam_id: u64) -> Option<String> {
+        let steam_id = steamworks::SteamId::from_raw(steam_id);
+        let friends = self.client.friends();
+
+        friends.request_user_information(steam_id, true);
+
+        let name = friends.get_friend(steam_id).name();
+        if name.is_empty() || name == "[unknown]" {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Get directory of workshop item, it does not have to exist.
+    pub fn get_item_dir(&self, item_id: &PublishedFileId) -> PathBuf {
+        self.get_workshop_dir().join(item_id.0.to_string())
+    }
+
     /// Get bytes size of workshop item (local, on disk)
     pub fn get_item_size(&self, item_id: &PublishedFileId) -> Option<u64> {
-        let item_dir = self.get_workshop_dir().join(item_id.0.to_string());
+        self.get_items_usage(&[*item_id], &DiskUsageWalker::new(), &mut Monitor::none())
+            .ok()
+            .and_then(|mut usage| usage.pop().flatten())
+            .map(|usage| usage.apparent_bytes)
+    }
 
-        if !item_dir.is_dir() {
-            return None;
-        }
+    /// Get disk usage of workshop items in one pass, `None` for items that are not on the disk.
+    ///
+    /// Items are reported while their files are counted, only items changed since they were
+    /// last measured are walked, see `SizeIndex`.
+    pub fn get_items_usage(
+        &self,
+        item_ids: &[PublishedFileId],
+        walker: &DiskUsageWalker,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<Option<DiskUsage>>, String> {
+        let workshop_dir = self.get_workshop_dir();
+        let dirs: Vec<(u64, PathBuf)> = item_ids
+            .iter()
+            .map(|id| (id.0, workshop_dir.join(id.0.to_string())))
+            .collect();
 
-        match fs_extra::dir::get_size(item_dir.to_str().unwrap()) {
-            Ok(s) => Some(s),
-            Err(_) => None,
-        }
+        self.size_index
+            .lock()
+            .map_err(|e| e.to_string())?
+            .measure(walker, &dirs, monitor)
     }
 
     /// Force removes workshop item from the disk, optionaly can also unsubscribe the item.
     pub fn remove_item(&self, item_id: &PublishedFileId, unsubscribe: bool) -> Result<(), String> {
-        let item_dir = self.get_workshop_dir().join(item_id.0.to_string());
+        self.remove_item_with(item_id, unsubscribe, &RemovalStrategy::Delete)
+    }
+
+    /// Same as `remove_item`, but the directory of the item is handled by `strategy`.
+    pub fn remove_item_with(
+        &self,
+        item_id: &PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+    ) -> Result<(), String> {
+        self.remove_item_monitored(item_id, unsubscribe, strategy, &mut Monitor::none())
+    }
+
+    /// Same as `remove_item_with`, deleted bytes are reported and cancellation is checked
+    /// between files.
+    ///
+    /// Files deleted before the cancellation stay deleted.
+    pub fn remove_item_monitored(
+        &self,
+        item_id: &PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+        monitor: &mut Monitor,
+    ) -> Result<(), String> {
+        monitor.report(Progress::ItemStarted { id: item_id.0 });
+
+        let result = self.remove(item_id, unsubscribe, strategy, monitor);
+        // even a failed removal can leave some of the files deleted
+        if let Ok(mut index) = self.size_index.lock() {
+            index.invalidate(item_id.0);
+        }
+        monitor.report(match &result {
+            Ok(_) => Progress::ItemRemoved { id: item_id.0 },
+            Err(e) => Progress::ItemFailed {
+                id: item_id.0,
+                error: e.clone(),
+            },
+        });
+
+        result
+    }
+
+    fn remove(
+        &self,
+        item_id: &PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+        monitor: &mut Monitor,
+    ) -> Result<(), String> {
+        let item_dir = self.get_item_dir(item_id);
 
         if !item_dir.is_dir() {
             return Err("Could not find workshop item".to_string());
         }
+        monitor.check()?;
 
         // try to unsubscribe from the item
         if unsubscribe {
@@ -105,9 +270,143 @@ impl WorkshopCleaner {
         };
 
         // remove item from disk
-        match fs_extra::dir::remove(item_dir.to_str().unwrap()) {
-            Ok(r) => Ok(r),
-            Err(e) => Err(e.to_string()),
+        match strategy {
+            RemovalStrategy::Delete => remove_dir(&item_dir, item_id.0, monitor),
+            RemovalStrategy::MoveTo(target) => move_dir_into(&item_dir, target),
         }
     }
 }
+
+/// Get workshop items installed in the workshop content directory of an app.
+pub fn read_installed_items(workshop_dir: &Path) -> Vec<PublishedFileId> {
+    // get all subdirectories in steam workshop dir
+    let directories: Vec<PathBuf> = match std::fs::read_dir(workshop_dir) {
+        Ok(entries) => entries
+            .filter_map(|r| r.ok())
+            .map(|r| r.path())
+            .filter(|p| p.is_dir())
+            .collect(),
+        Err(_) => return vec![],
+    };
+
+    directories
+        .into_iter()
+        .filter_map(|p| p.to_published_file_id())
+        .collect()
+}
+
+/// Get directories in the workshop content directory of an app that are not named by an item ID.
+pub fn read_unknown_folders(workshop_dir: &Path) -> Vec<PathBuf> {
+    match std::fs::read_dir(workshop_dir) {
+        Ok(entries) => entries
+            .filter_map(|r| r.ok())
+            .map(|r| r.path())
+            .filter(|p| p.is_dir() && p.to_published_file_id().is_none())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Move directory into `target`, replacing a directory of the same name already there.
+pub fn move_dir_into(dir: &Path, target: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(target).map_err(|e| e.to_string())?;
+
+    let mut options = fs_extra::dir::CopyOptions::new();
+    options.overwrite = true;
+
+    fs_extra::dir::move_dir(dir, target, &options)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Get bytes size of a directory, `None` if it does not exist or could not be read.
+pub fn get_dir_size(dir: &Path) -> Option<u64> {
+    DiskUsageWalker::new()
+        .measure_dir(dir, &mut Monitor::none())
+        .ok()
+        .flatten()
+        .map(|usage| usage.apparent_bytes)
+}
+
+/// Delete a directory file by file, deleted bytes are reported as `id`.
+pub fn remove_dir(dir: &Path, id: u64, monitor: &mut Monitor) -> Result<(), String> {
+    let mut removed = (0, 0);
+
+    remove_dir_contents(dir, id, monitor, &mut removed)?;
+    std::fs::remove_dir(dir).map_err(|e| e.to_string())
+}
+
+fn remove_dir_contents(
+    dir: &Path,
+    id: u64,
+    monitor: &mut Monitor,
+    removed: &mut (u64, u64),
+) -> Result<(), String> {
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        monitor.check()?;
+        let entry = entry.map_err(|e| e.to_string())?;
+        let metadata = entry.metadata().map_err(|e| e.to_string())?;
+        let path = entry.path();
+
+        if metadata.is_dir() {
+            remove_dir_contents(&path, id, monitor, removed)?;
+            std::fs::remove_dir(&path).map_err(|e| e.to_string())?;
+            continue;
+        }
+
+        std::fs::remove_file(&path)
+            .or_else(|e| match metadata.file_type().is_symlink() {
+                // directory symlinks on Windows
+                true => std::fs::remove_dir(&path),
+                false => Err(e),
+            })
+            .map_err(|e| e.to_string())?;
+        removed.0 += metadata.len();
+        removed.1 += 1;
+        monitor.report(Progress::BytesCounted {
+            id,
+            bytes: removed.0,
+            files: removed.1,
+        });
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_remove_dir() {
+    use crate::progress::{CancelToken, CANCELLED};
+
+    let dir = crate::test_support::test_dir("remove");
+    std::fs::create_dir_all(dir.join("addons")).unwrap();
+    std::fs::write(dir.join("addons").join("main.pbo"), vec![0; 300]).unwrap();
+    std::fs::write(dir.join("mod.cpp"), vec![0; 20]).unwrap();
+
+    assert_eq!(get_dir_size(&dir), Some(320));
+
+    let token = CancelToken::new();
+    token.cancel();
+    let mut monitor = Monitor::new(token, |_| ());
+    assert_eq!(
+        remove_dir(&dir, 1, &mut monitor),
+        Err(CANCELLED.to_string())
+    );
+    assert!(dir.is_dir());
+
+    let mut reported = vec![];
+    remove_dir(
+        &dir,
+        1,
+        &mut Monitor::new(CancelToken::new(), |p| reported.push(p)),
+    )
+    .unwrap();
+    assert!(!dir.exists());
+    assert!(matches!(
+        reported[0],
+        Progress::BytesCounted {
+            id: 1,
+            files: 1,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,527 @@
+//! Disk usage of item directories, measured by several threads in a single pass.
+//!
+//! Each directory is a unit of work, so a large item is shared by the threads as well as many
+//! small ones. Every file is read once and counts towards apparent size, allocated size, file
+//! count and largest files of its item.
+
+use std::{
+    collections::HashSet,
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Sender},
+        Condvar, Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    item_files::DiskEntry,
+    progress::{CancelToken, Monitor, Progress, CANCELLED},
+};
+
+/// Block size used to estimate allocated size where the platform does not report it.
+#[cfg(not(unix))]
+const ESTIMATED_BLOCK_SIZE: u64 = 4096;
+
+/// Which size of the files counts as size of the item.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeMode {
+    /// Length of the files, what file managers usually show.
+    #[default]
+    Apparent,
+    /// Blocks allocated on the disk, space freed by removal.
+    Allocated,
+}
+
+/// Disk usage of a directory and everything in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// Sum of file lengths.
+    pub apparent_bytes: u64,
+    /// Bytes of blocks allocated for the files, estimated on platforms that don't report it.
+    pub allocated_bytes: u64,
+    pub files: u64,
+    /// Largest files at any depth, largest first, sized by the walker mode.
+    pub largest_files: Vec<DiskEntry>,
+}
+
+impl DiskUsage {
+    pub fn bytes(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+
+    fn add(&mut self, other: DiskUsage, limit: usize) {
+        self.apparent_bytes += other.apparent_bytes;
+        self.allocated_bytes += other.allocated_bytes;
+        self.files += other.files;
+        self.largest_files.extend(other.largest_files);
+        keep_largest(&mut self.largest_files, limit);
+    }
+}
+
+/// Walker of item directories, configured with `with_*` methods.
+///
+/// Hardlinked files are counted once per walked directory. Symlinks are counted by their own
+/// size unless they are followed, followed directories are walked once per walked directory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsageWalker {
+    mode: SizeMode,
+    follow_symlinks: bool,
+    largest_files: usize,
+    threads: usize,
+}
+
+impl Default for DiskUsageWalker {
+    fn default() -> Self {
+        DiskUsageWalker {
+            mode: SizeMode::default(),
+            follow_symlinks: false,
+            largest_files: 10,
+            threads: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+        }
+    }
+}
+
+/// Directory waiting to be read, `item` is index of the walked directory it belongs to.
+struct Task {
+    item: usize,
+    dir: PathBuf,
+}
+
+struct Queue {
+    tasks: Vec<Task>,
+    /// Directories queued or being read, per item.
+    pending: Vec<usize>,
+    /// All directories are read or the walk was cancelled.
+    done: bool,
+}
+
+enum Message {
+    Counted(usize, DiskUsage),
+    /// Directory of the item could not be read, its usage is unknown.
+    Failed(usize, String),
+    /// All directories of the item are read.
+    Finished(usize),
+}
+
+/// State shared by the threads of one walk.
+struct Walk<'a> {
+    walker: &'a DiskUsageWalker,
+    roots: Vec<&'a Path>,
+    token: CancelToken,
+    queue: Mutex<Queue>,
+    ready: Condvar,
+    /// Files with several hardlinks and followed directories seen so far, with their item.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    seen: Mutex<HashSet<(usize, u64, u64)>>,
+}
+
+impl DiskUsageWalker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_mode(mut self, mode: SizeMode) -> Self {
+        self.mode = mode;
+
+        self
+    }
+
+    /// Count files and directories symlinks point to, instead of the symlinks.
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+
+        self
+    }
+
+    /// Number of largest files kept for each directory.
+    pub fn with_largest_files(mut self, limit: usize) -> Self {
+        self.largest_files = limit;
+
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+
+        self
+    }
+
+    pub fn mode(&self) -> SizeMode {
+        self.mode
+    }
+
+    /// Usage measured by `other` is what this walker would measure, up to the largest files.
+    pub fn reuses(&self, other: &DiskUsageWalker) -> bool {
+        self.mode == other.mode
+            && self.follow_symlinks == other.follow_symlinks
+            && self.largest_files <= other.largest_files
+    }
+
+    /// Keep only as many largest files as this walker would.
+    pub fn truncate(&self, usage: &mut DiskUsage) {
+        usage.largest_files.truncate(self.largest_files);
+    }
+
+    /// Measure a single directory, `None` if it does not exist or could not be read.
+    pub fn measure_dir(
+        &self,
+        dir: &Path,
+        monitor: &mut Monitor,
+    ) -> Result<Option<DiskUsage>, String> {
+        let mut usage = self.measure(&[(0, dir.to_path_buf())], monitor)?;
+
+        Ok(usage.pop().flatten())
+    }
+
+    /// Measure directories of the items in one pass, results are in order of `dirs`.
+    ///
+    /// Items are reported by their IDs while they are walked, `None` is the usage of a
+    /// directory that does not exist or could not be read.
+    pub fn measure(
+        &self,
+        dirs: &[(u64, PathBuf)],
+        monitor: &mut Monitor,
+    ) -> Result<Vec<Option<DiskUsage>>, String> {
+        let mut errors: Vec<Option<String>> = vec![None; dirs.len()];
+        let mut usage: Vec<Option<DiskUsage>> = dirs
+            .iter()
+            .map(|(_, dir)| Some(DiskUsage::default()).filter(|_| dir.is_dir()))
+            .collect();
+        let tasks: Vec<Task> = dirs
+            .iter()
+            .enumerate()
+            .filter(|(item, _)| usage[*item].is_some())
+            .map(|(item, (_, dir))| Task {
+                item,
+                dir: dir.clone(),
+            })
+            .collect();
+
+        let walk = Walk {
+            walker: self,
+            roots: dirs.iter().map(|(_, dir)| dir.as_path()).collect(),
+            token: monitor.token().clone(),
+            queue: Mutex::new(Queue {
+                pending: (0..dirs.len())
+                    .map(|item| usage[item].is_some() as usize)
+                    .collect(),
+                done: tasks.is_empty(),
+                tasks,
+            }),
+            ready: Condvar::new(),
+            seen: Mutex::new(HashSet::new()),
+        };
+        for (item, (id, _)) in dirs.iter().enumerate() {
+            if usage[item].is_some() {
+                monitor.report(Progress::ItemStarted { id: *id });
+            }
+        }
+
+        let (sender, messages) = channel();
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads {
+                let sender = sender.clone();
+                let walk = &walk;
+                scope.spawn(move || walk.run(sender));
+            }
+            drop(sender);
+
+            for message in messages {
+                match message {
+                    Message::Counted(item, counted) => {
+                        if let Some(total) = usage[item].as_mut() {
+                            total.add(counted, self.largest_files);
+                            monitor.report(Progress::BytesCounted {
+                                id: dirs[item].0,
+                                bytes: total.bytes(self.mode),
+                                files: total.files,
+                            });
+                        }
+                    }
+                    Message::Failed(item, error) => {
+                        usage[item] = None;
+                        errors[item].get_or_insert(error);
+                    }
+                    Message::Finished(item) => {
+                        let id = dirs[item].0;
+                        monitor.report(match (&usage[item], &errors[item]) {
+                            (Some(usage), _) => Progress::ItemMeasured {
+                                id,
+                                bytes: usage.bytes(self.mode),
+                                files: usage.files,
+                            },
+                            (None, error) => Progress::ItemFailed {
+                                id,
+                                error: error.clone().unwrap_or_default(),
+                            },
+                        });
+                    }
+                }
+            }
+        });
+
+        match walk.token.is_cancelled() {
+            true => Err(CANCELLED.to_string()),
+            false => Ok(usage),
+        }
+    }
+}
+
+impl Walk<'_> {
+    /// Read queued directories until all are read, run by each thread.
+    fn run(&self, sender: Sender<Message>) {
+        while let Some(task) = self.next_task() {
+            let mut subdirs = vec![];
+            let message = match self.read_dir(&task, &mut subdirs) {
+                Ok(counted) => Message::Counted(task.item, counted),
+                Err(e) => Message::Failed(task.item, e),
+            };
+            let _ = sender.send(message);
+
+            self.finish_task(task.item, subdirs, &sender);
+        }
+    }
+
+    fn next_task(&self) -> Option<Task> {
+        let mut queue = self.queue.lock().ok()?;
+
+        loop {
+            if self.token.is_cancelled() {
+                queue.done = true;
+                self.ready.notify_all();
+            }
+            if queue.done {
+                return None;
+            }
+            if let Some(task) = queue.tasks.pop() {
+                return Some(task);
+            }
+
+            queue = self.ready.wait(queue).ok()?;
+        }
+    }
+
+    /// Queue subdirectories of the read directory, reports the item once it's all read.
+    fn finish_task(&self, item: usize, subdirs: Vec<PathBuf>, sender: &Sender<Message>) {
+        let mut queue = match self.queue.lock() {
+            Ok(queue) => queue,
+            Err(_) => return,
+        };
+
+        queue.pending[item] += subdirs.len();
+        queue.pending[item] -= 1;
+        queue
+            .tasks
+            .extend(subdirs.into_iter().map(|dir| Task { item, dir }));
+
+        if queue.pending[item] == 0 {
+            let _ = sender.send(Message::Finished(item));
+            queue.done = queue.pending.iter().all(|p| *p == 0);
+        }
+        self.ready.notify_all();
+    }
+
+    /// Count files of the directory, its subdirectories are added to `subdirs`.
+    fn read_dir(&self, task: &Task, subdirs: &mut Vec<PathBuf>) -> Result<DiskUsage, String> {
+        let mut usage = DiskUsage::default();
+
+        for entry in fs::read_dir(&task.dir).map_err(|e| e.to_string())? {
+            if self.token.is_cancelled() {
+                return Err(CANCELLED.to_string());
+            }
+
+            let entry = entry.map_err(|e| e.to_string())?;
+            let path = entry.path();
+            let mut metadata = entry.metadata().map_err(|e| e.to_string())?;
+            let is_link = metadata.file_type().is_symlink();
+            if is_link && self.walker.follow_symlinks {
+                // broken symlinks are counted as they are
+                metadata = fs::metadata(&path).unwrap_or(metadata);
+            }
+
+            if !self.first_visit(task.item, &metadata, is_link) {
+                continue;
+            }
+            if metadata.is_dir() {
+                subdirs.push(path);
+                continue;
+            }
+
+            let (apparent, allocated) = (metadata.len(), allocated_size(&metadata));
+            usage.apparent_bytes += apparent;
+            usage.allocated_bytes += allocated;
+            usage.files += 1;
+            usage.largest_files.push(DiskEntry {
+                path: path
+                    .strip_prefix(self.roots[task.item])
+                    .unwrap_or(&path)
+                    .to_path_buf(),
+                size: match self.walker.mode {
+                    SizeMode::Apparent => apparent,
+                    SizeMode::Allocated => allocated,
+                },
+            });
+        }
+        keep_largest(&mut usage.largest_files, self.walker.largest_files);
+
+        Ok(usage)
+    }
+
+    /// Entry was not counted for the item yet, only hardlinked files and directories that could
+    /// be reached through symlinks are looked up.
+    #[cfg(unix)]
+    fn first_visit(&self, item: usize, metadata: &Metadata, _is_link: bool) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        let linked = match metadata.is_dir() {
+            true => self.walker.follow_symlinks,
+            false => metadata.nlink() > 1,
+        };
+        if !linked {
+            return true;
+        }
+
+        match self.seen.lock() {
+            Ok(mut seen) => seen.insert((item, metadata.dev(), metadata.ino())),
+            Err(_) => true,
+        }
+    }
+
+    /// Without inode numbers hardlinks can't be told apart, directory symlinks are not walked.
+    #[cfg(not(unix))]
+    fn first_visit(&self, _item: usize, metadata: &Metadata, is_link: bool) -> bool {
+        !(metadata.is_dir() && is_link)
+    }
+}
+
+#[cfg(unix)]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    (metadata.len() + ESTIMATED_BLOCK_SIZE - 1) / ESTIMATED_BLOCK_SIZE * ESTIMATED_BLOCK_SIZE
+}
+
+fn keep_largest(entries: &mut Vec<DiskEntry>, limit: usize) {
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    entries.truncate(limit);
+}
+
+#[cfg(test)]
+fn write_file(path: &Path, len: usize) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0; len]).unwrap();
+}
+
+#[test]
+fn test_measure_dirs() {
+    let dir = crate::test_support::test_dir("disk-usage");
+    write_file(&dir.join("1/addons/main.pbo"), 3000);
+    write_file(&dir.join("1/addons/ui.pbo"), 500);
+    write_file(&dir.join("1/mod.cpp"), 20);
+    write_file(&dir.join("2/mod.cpp"), 40);
+    fs::create_dir_all(dir.join("3")).unwrap();
+
+    let mut reported = vec![];
+    let mut monitor = Monitor::new(CancelToken::new(), |p| reported.push(p));
+    let dirs: Vec<(u64, PathBuf)> = (1..=4).map(|id| (id, dir.join(id.to_string()))).collect();
+    let usage = DiskUsageWalker::new()
+        .with_largest_files(2)
+        .with_threads(3)
+        .measure(&dirs, &mut monitor)
+        .unwrap();
+    drop(monitor);
+
+    let first = usage[0].as_ref().unwrap();
+    assert_eq!((first.apparent_bytes, first.files), (3520, 3));
+    assert!(first.allocated_bytes >= 3 * 512 || first.allocated_bytes == 0);
+    assert_eq!(
+        first.largest_files,
+        [
+            DiskEntry {
+                path: PathBuf::from("addons").join("main.pbo"),
+                size: 3000
+            },
+            DiskEntry {
+                path: PathBuf::from("addons").join("ui.pbo"),
+                size: 500
+            },
+        ]
+    );
+    assert_eq!(usage[1].as_ref().map(|u| u.apparent_bytes), Some(40));
+    assert_eq!(usage[2], Some(DiskUsage::default()));
+    assert_eq!(usage[3], None);
+
+    assert!(reported.contains(&Progress::ItemMeasured {
+        id: 1,
+        bytes: 3520,
+        files: 3
+    }));
+    assert!(reported.contains(&Progress::ItemMeasured {
+        id: 3,
+        bytes: 0,
+        files: 0
+    }));
+    assert!(!reported.iter().any(|p| matches!(
+        p,
+        Progress::ItemStarted { id: 4 } | Progress::ItemMeasured { id: 4, .. }
+    )));
+
+    let allocated = DiskUsageWalker::new()
+        .with_mode(SizeMode::Allocated)
+        .measure_dir(&dir.join("1"), &mut Monitor::none())
+        .unwrap()
+        .unwrap();
+    assert_eq!(allocated.bytes(SizeMode::Allocated), first.allocated_bytes);
+    assert_eq!(allocated.bytes(SizeMode::Apparent), 3520);
+
+    let token = CancelToken::new();
+    token.cancel();
+    assert_eq!(
+        DiskUsageWalker::new().measure(&dirs, &mut Monitor::new(token, |_| ())),
+        Err(CANCELLED.to_string())
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_measure_links() {
+    let dir = crate::test_support::test_dir("disk-usage-links");
+    write_file(&dir.join("item/data/main.pbo"), 1000);
+    fs::hard_link(dir.join("item/data/main.pbo"), dir.join("item/copy.pbo")).unwrap();
+    std::os::unix::fs::symlink(dir.join("item/data"), dir.join("item/linked")).unwrap();
+    write_file(&dir.join("other/big.pbo"), 5000);
+    std::os::unix::fs::symlink(dir.join("other"), dir.join("item/other")).unwrap();
+
+    let measure = |walker: DiskUsageWalker| {
+        walker
+            .measure_dir(&dir.join("item"), &mut Monitor::none())
+            .unwrap()
+            .unwrap()
+    };
+
+    // hardlinked file once, symlinks by their own size
+    let usage = measure(DiskUsageWalker::new());
+    assert_eq!(usage.files, 3);
+    let link_len = fs::symlink_metadata(dir.join("item/linked")).unwrap().len()
+        + fs::symlink_metadata(dir.join("item/other")).unwrap().len();
+    assert_eq!(usage.apparent_bytes, 1000 + link_len);
+
+    // followed symlink to a directory already walked is not counted again
+    let usage = measure(DiskUsageWalker::new().with_follow_symlinks(true));
+    assert_eq!((usage.apparent_bytes, usage.files), (6000, 2));
+    assert_eq!(usage.largest_files[0].path, PathBuf::from("other/big.pbo"));
+}
@@ -0,0 +1,652 @@
+//! Steam access of a single app in a short-lived helper process.
+//!
+//! Steam client can be initialized only once per process and only for one app, so to work with
+//! several apps the application starts itself again as a helper for each of them. The helper
+//! reads requests from stdin and writes responses to stdout, one JSON document per line.
+//!
+//! Long running requests write progress lines before their response, a `cancel` request sent
+//! meanwhile stops them between files.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{mpsc::channel, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cleaner::{get_dir_size, ItemState, RemovalStrategy, WorkshopCleaner},
+    disk_usage::{DiskUsage, DiskUsageWalker},
+    progress::{CancelToken, Monitor, Progress},
+    AppId, PublishedFileId,
+};
+
+/// First argument of the process that makes it run as a helper, second one is the app ID.
+pub const HELPER_ARG: &str = "--workshop-cleaner-helper";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+enum Request {
+    Items,
+    UnknownFolders,
+    WorkshopDir,
+    ItemsUsage {
+        ids: Vec<u64>,
+        walker: DiskUsageWalker,
+    },
+    UserName {
+        steam_id: u64,
+    },
+    RemoveItem {
+        id: u64,
+        unsubscribe: bool,
+        strategy: RemovalStrategy,
+    },
+    /// Cancel the running request, it's not answered.
+    Cancel,
+    Shutdown,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "snake_case")]
+enum Response {
+    /// Steam client was initialized, helper accepts requests.
+    Ready,
+    /// Progress of the running request, its response follows later.
+    Progress {
+        progress: Progress,
+    },
+    Items {
+        items: Vec<(u64, ItemState)>,
+    },
+    /// Folder names with their bytes size.
+    UnknownFolders {
+        folders: Vec<(String, Option<u64>)>,
+    },
+    WorkshopDir {
+        path: PathBuf,
+    },
+    ItemsUsage {
+        usage: Vec<Option<DiskUsage>>,
+    },
+    UserName {
+        name: Option<String>,
+    },
+    Removed,
+    Error {
+        message: String,
+    },
+}
+
+/// Run as a helper if the process was started as one, returns exit code of the helper.
+///
+/// Should be called at the very start of `main`, before anything else is done.
+pub fn run_if_requested() -> Option<i32> {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some(HELPER_ARG) {
+        return None;
+    }
+
+    let app_id = match args.next().and_then(|id| id.parse().ok()) {
+        Some(app_id) => AppId(app_id),
+        None => return Some(2),
+    };
+    let stdout = std::io::stdout();
+
+    match run(app_id, BufReader::new(std::io::stdin()), stdout.lock()) {
+        Ok(_) => Some(0),
+        Err(_) => Some(1),
+    }
+}
+
+fn run(
+    app_id: AppId,
+    input: impl BufRead + Send + 'static,
+    mut output: impl Write,
+) -> Result<(), String> {
+    let cleaner = match crate::init(app_id) {
+        Ok(cleaner) => {
+            write_line(&mut output, &Response::Ready)?;
+            cleaner
+        }
+        Err(message) => {
+            write_line(&mut output, &Response::Error { message })?;
+            return Err("Failed to init Steam client".to_string());
+        }
+    };
+
+    serve(&cleaner, input, output)
+}
+
+/// Answer requests read from `input` until a shutdown request or the end of the input.
+fn serve(
+    handler: &impl RequestHandler,
+    input: impl BufRead + Send + 'static,
+    mut output: impl Write,
+) -> Result<(), String> {
+    // requests are read on another thread, so a cancel request can arrive while one runs;
+    // every request gets its token when it's read, a cancel request cancels the last one
+    let (sender, requests) = channel();
+    std::thread::spawn(move || {
+        let mut last = CancelToken::new();
+        for line in input.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            match serde_json::from_str(&line) {
+                Ok(Request::Cancel) => last.cancel(),
+                request => {
+                    last = CancelToken::new();
+                    if sender.send((request, last.clone())).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    for (request, token) in requests {
+        let response = match request {
+            Ok(Request::Shutdown) => break,
+            Ok(request) => {
+                let mut monitor = Monitor::new(token, |progress| {
+                    let _ = write_line(&mut output, &Response::Progress { progress });
+                });
+                handler.handle(request, &mut monitor)
+            }
+            Err(e) => Response::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+
+        write_line(&mut output, &response)?;
+    }
+
+    Ok(())
+}
+
+/// Answers helper requests, the Steam client of the app outside of tests.
+trait RequestHandler {
+    fn handle(&self, request: Request, monitor: &mut Monitor) -> Response;
+}
+
+impl RequestHandler for WorkshopCleaner {
+    fn handle(&self, request: Request, monitor: &mut Monitor) -> Response {
+        match request {
+            Request::Items => match self.get_installed_items_monitored(monitor) {
+                Ok(items) => Response::Items {
+                    items: items.into_iter().map(|(id, state)| (id.0, state)).collect(),
+                },
+                Err(message) => Response::Error { message },
+            },
+            Request::UnknownFolders => Response::UnknownFolders {
+                folders: self
+                    .get_unknown_folders()
+                    .iter()
+                    .filter_map(|p| {
+                        let name = p.file_name()?.to_string_lossy().into_owned();
+
+                        Some((name, get_dir_size(p)))
+                    })
+                    .collect(),
+            },
+            Request::WorkshopDir => Response::WorkshopDir {
+                path: self.get_workshop_dir(),
+            },
+            Request::ItemsUsage { ids, walker } => {
+                let ids: Vec<PublishedFileId> = ids.into_iter().map(PublishedFileId).collect();
+
+                match self.get_items_usage(&ids, &walker, monitor) {
+                    Ok(usage) => Response::ItemsUsage { usage },
+                    Err(message) => Response::Error { message },
+                }
+            }
+            Request::UserName { steam_id } => Response::UserName {
+                name: self.get_user_name(steam_id),
+            },
+            Request::RemoveItem {
+                id,
+                unsubscribe,
+                strategy,
+            } => match self.remove_item_monitored(
+                &PublishedFileId(id),
+                unsubscribe,
+                &strategy,
+                monitor,
+            ) {
+                Ok(_) => Response::Removed,
+                Err(message) => Response::Error { message },
+            },
+            Request::Cancel | Request::Shutdown => {
+                unreachable!("Cancel and shutdown are handled by the request loop")
+            }
+        }
+    }
+}
+
+fn write_line<T: Serialize>(output: &mut impl Write, value: &T) -> Result<(), String> {
+    let line = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    writeln!(output, "{}", line).map_err(|e| e.to_string())?;
+
+    output.flush().map_err(|e| e.to_string())
+}
+
+/// Read next JSON line, other lines are skipped as Steam API writes its own messages to stdout.
+fn read_line<T: for<'de> Deserialize<'de>>(input: &mut impl BufRead) -> Result<T, String> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match input.read_line(&mut line) {
+            Ok(0) => return Err("Helper process exited".to_string()),
+            Ok(_) if !line.starts_with('{') => continue,
+            Ok(_) => {
+                return serde_json::from_str(&line).map_err(|e| format!("Invalid message: {}", e))
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+/// Write the request and read its progress lines and response.
+///
+/// Cancellation of the monitor is passed to the helper after the next progress line.
+fn exchange(
+    stdin: &mut impl Write,
+    stdout: &mut impl BufRead,
+    request: &Request,
+    monitor: &mut Monitor,
+) -> Result<Response, String> {
+    write_line(stdin, request)?;
+    let mut cancel_sent = false;
+    loop {
+        match read_line(stdout)? {
+            Response::Progress { progress } => {
+                if !cancel_sent && monitor.token().is_cancelled() {
+                    write_line(stdin, &Request::Cancel)?;
+                    cancel_sent = true;
+                }
+                monitor.report(progress);
+            }
+            Response::Error { message } => return Err(message),
+            response => return Ok(response),
+        }
+    }
+}
+
+/// Workshop cleaner of an app running in a helper process.
+///
+/// Requests are sent one at a time, so the cleaner can be shared between threads.
+/// The helper process is shut down when the cleaner is dropped.
+pub struct HelperCleaner {
+    app_id: AppId,
+    child: Child,
+    pipe: Mutex<(ChildStdin, BufReader<ChildStdout>)>,
+}
+
+impl HelperCleaner {
+    /// Start helper process for the app, waits until the helper initialized Steam client.
+    pub fn spawn(app_id: AppId) -> Result<Self, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut command = Command::new(exe);
+        // Steam API reads the app ID from the environment, no shared file is written
+        for var in crate::STEAM_APP_ID_VARS.iter() {
+            command.env(var, app_id.0.to_string());
+        }
+        let mut child = command
+            .arg(HELPER_ARG)
+            .arg(app_id.0.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not start helper process: {}", e))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+        match read_line(&mut stdout) {
+            Ok(Response::Ready) => Ok(HelperCleaner {
+                app_id,
+                child,
+                pipe: Mutex::new((stdin, stdout)),
+            }),
+            Ok(Response::Error { message }) => {
+                let _ = child.wait();
+                Err(message)
+            }
+            Ok(response) => {
+                let _ = child.kill();
+                Err(format!("Unexpected response {:?}", response))
+            }
+            Err(e) => {
+                let _ = child.kill();
+                Err(e)
+            }
+        }
+    }
+
+    pub fn app_id(&self) -> AppId {
+        self.app_id
+    }
+
+    /// Get all installed workshop items together with their state.
+    pub fn get_installed_items_with_state(
+        &self,
+    ) -> Result<Vec<(PublishedFileId, ItemState)>, String> {
+        self.get_installed_items_monitored(&mut Monitor::none())
+    }
+
+    /// Same as `get_installed_items_with_state`, each item is reported as started.
+    pub fn get_installed_items_monitored(
+        &self,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<(PublishedFileId, ItemState)>, String> {
+        match self.request_monitored(&Request::Items, monitor)? {
+            Response::Items { items } => Ok(items
+                .into_iter()
+                .map(|(id, state)| (PublishedFileId(id), state))
+                .collect()),
+            response => Err(format!("Unexpected response {:?}", response)),
+        }
+    }
+
+    /// Get names and sizes of directories in the workshop content directory that are not items.
+    pub fn get_unknown_folders(&self) -> Result<Vec<(String, Option<u64>)>, String> {
+        match self.request(&Request::UnknownFolders)? {
+            Response::UnknownFolders { folders } => Ok(folders),
+            response => Err(format!("Unexpected response {:?}", response)),
+        }
+    }
+
+    /// Get workshop content directory of the app, items are its subdirectories.
+    pub fn get_workshop_dir(&self) -> Result<PathBuf, String> {
+        match self.request(&Request::WorkshopDir)? {
+            Response::WorkshopDir { path } => Ok(path),
+            response => Err(format!("Unexpected response {:?}", response)),
+        }
+    }
+
+    /// Get bytes size of workshop item (local, on disk)
+    pub fn get_item_size(&self, item_id: &PublishedFileId) -> Option<u64> {
+        self.get_items_usage(&[*item_id], &DiskUsageWalker::new(), &mut Monitor::none())
+            .ok()
+            .and_then(|mut usage| usage.pop().flatten())
+            .map(|usage| usage.apparent_bytes)
+    }
+
+    /// Get disk usage of workshop items in one pass, `None` for items that are not on the disk.
+    pub fn get_items_usage(
+        &self,
+        item_ids: &[PublishedFileId],
+        walker: &DiskUsageWalker,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<Option<DiskUsage>>, String> {
+        let request = Request::ItemsUsage {
+            ids: item_ids.iter().map(|id| id.0).collect(),
+            walker: walker.clone(),
+        };
+
+        match self.request_monitored(&request, monitor)? {
+            Response::ItemsUsage { usage } => Ok(usage),
+            response => Err(format!("Unexpected response {:?}", response)),
+        }
+    }
+
+    /// Get persona name of a Steam user, `None` if Steam does not know it yet.
+    pub fn get_user_name(&self, steam_id: u64) -> Option<String> {
+        match self.request(&Request::UserName { steam_id }) {
+            Ok(Response::UserName { name }) => name,
+            _ => None,
+        }
+    }
+
+    /// Force removes workshop item from the disk, optionaly can also unsubscribe the item.
+    pub fn remove_item(
+        &self,
+        item_id: &PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+    ) -> Result<(), String> {
+        self.remove_item_monitored(item_id, unsubscribe, strategy, &mut Monitor::none())
+    }
+
+    /// Same as `remove_item`, deleted bytes are reported and cancellation is checked between
+    /// files.
+    pub fn remove_item_monitored(
+        &self,
+        item_id: &PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+        monitor: &mut Monitor,
+    ) -> Result<(), String> {
+        let request = Request::RemoveItem {
+            id: item_id.0,
+            unsubscribe,
+            strategy: strategy.clone(),
+        };
+
+        match self.request_monitored(&request, monitor)? {
+            Response::Removed => Ok(()),
+            response => Err(format!("Unexpected response {:?}", response)),
+        }
+    }
+
+    fn request(&self, request: &Request) -> Result<Response, String> {
+        self.request_monitored(request, &mut Monitor::none())
+    }
+
+    /// Send the request and report its progress until the response arrives.
+    fn request_monitored(
+        &self,
+        request: &Request,
+        monitor: &mut Monitor,
+    ) -> Result<Response, String> {
+        monitor.check()?;
+        let mut pipe = self.pipe.lock().map_err(|e| e.to_string())?;
+        let (stdin, stdout) = &mut *pipe;
+
+        exchange(stdin, stdout, request, monitor)
+    }
+}
+
+impl Drop for HelperCleaner {
+    fn drop(&mut self) {
+        let shutdown = match self.pipe.get_mut() {
+            Ok((stdin, _)) => write_line(stdin, &Request::Shutdown),
+            Err(_) => Err("Helper pipe is poisoned".to_string()),
+        };
+
+        if shutdown.is_err() {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_protocol_lines() {
+    let mut buffer = vec![];
+    write_line(
+        &mut buffer,
+        &Request::RemoveItem {
+            id: 450814997,
+            unsubscribe: true,
+            strategy: RemovalStrategy::Delete,
+        },
+    )
+    .unwrap();
+    write_line(
+        &mut buffer,
+        &Response::Items {
+            items: vec![(450814997, ItemState::Unsubscribed)],
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer.clone()).unwrap(),
+        "{\"request\":\"remove_item\",\"id\":450814997,\"unsubscribe\":true,\"strategy\":\"delete\"}\n\
+         {\"response\":\"items\",\"items\":[[450814997,\"unsubscribed\"]]}\n"
+    );
+
+    buffer.splice(
+        0..0,
+        b"Setting breakpad minidump AppID = 107410\n"
+            .iter()
+            .copied(),
+    );
+    let mut input = &buffer[..];
+    assert_eq!(
+        read_line::<Request>(&mut input).unwrap(),
+        Request::RemoveItem {
+            id: 450814997,
+            unsubscribe: true,
+            strategy: RemovalStrategy::Delete
+        }
+    );
+    assert_eq!(
+        read_line::<Response>(&mut input).unwrap(),
+        Response::Items {
+            items: vec![(450814997, ItemState::Unsubscribed)]
+        }
+    );
+    assert!(read_line::<Response>(&mut input).is_err());
+}
+
+#[test]
+fn test_protocol_progress() {
+    let mut buffer = vec![];
+    write_line(
+        &mut buffer,
+        &Response::Progress {
+            progress: Progress::BytesCounted {
+                id: 450814997,
+                bytes: 2048,
+                files: 3,
+            },
+        },
+    )
+    .unwrap();
+    write_line(&mut buffer, &Request::Cancel).unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "{\"response\":\"progress\",\"progress\":{\"event\":\"bytes_counted\",\"id\":450814997,\"bytes\":2048,\"files\":3}}\n\
+         {\"request\":\"cancel\"}\n"
+    );
+}
+
+#[cfg(test)]
+struct FakeHandler;
+
+#[cfg(test)]
+impl RequestHandler for FakeHandler {
+    fn handle(&self, request: Request, monitor: &mut Monitor) -> Response {
+        match request {
+            Request::Items => {
+                monitor.report(Progress::ItemStarted { id: 1 });
+                Response::Items {
+                    items: vec![(1, ItemState::Unsubscribed)],
+                }
+            }
+            // runs until it's cancelled
+            Request::RemoveItem { id, .. } => {
+                let started = std::time::Instant::now();
+                while started.elapsed() < std::time::Duration::from_secs(10) {
+                    monitor.report(Progress::ItemStarted { id });
+                    if let Err(message) = monitor.check() {
+                        return Response::Error { message };
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                Response::Removed
+            }
+            request => Response::Error {
+                message: format!("Unexpected request {:?}", request),
+            },
+        }
+    }
+}
+
+#[test]
+fn test_serve() {
+    let mut input = vec![];
+    write_line(&mut input, &Request::Items).unwrap();
+    write_line(
+        &mut input,
+        &Request::RemoveItem {
+            id: 2,
+            unsubscribe: false,
+            strategy: RemovalStrategy::Delete,
+        },
+    )
+    .unwrap();
+    write_line(&mut input, &Request::Cancel).unwrap();
+    input.extend(b"not a request\n");
+    write_line(&mut input, &Request::Shutdown).unwrap();
+    write_line(&mut input, &Request::Items).unwrap();
+
+    let mut output = vec![];
+    serve(&FakeHandler, std::io::Cursor::new(input), &mut output).unwrap();
+    let mut responses: Vec<Response> = output
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+
+    // the removal reports progress until the cancel request is read
+    let removal_progress = Response::Progress {
+        progress: Progress::ItemStarted { id: 2 },
+    };
+    assert!(responses.contains(&removal_progress));
+    responses.retain(|r| r != &removal_progress);
+
+    assert_eq!(responses.len(), 4);
+    assert_eq!(
+        responses[..3],
+        [
+            Response::Progress {
+                progress: Progress::ItemStarted { id: 1 }
+            },
+            Response::Items {
+                items: vec![(1, ItemState::Unsubscribed)]
+            },
+            Response::Error {
+                message: crate::progress::CANCELLED.to_string()
+            },
+        ]
+    );
+    assert!(
+        matches!(&responses[3], Response::Error { message } if message.starts_with("Invalid request"))
+    );
+}
+
+#[test]
+fn test_exchange_cancel() {
+    let mut stdout = b"[S_API] SteamAPI_Init(): Loaded\n".to_vec();
+    for id in 1..=3 {
+        let progress = Progress::ItemStarted { id };
+        write_line(&mut stdout, &Response::Progress { progress }).unwrap();
+    }
+    let message = crate::progress::CANCELLED.to_string();
+    write_line(&mut stdout, &Response::Error { message }).unwrap();
+
+    let token = CancelToken::new();
+    let mut reported = vec![];
+    let mut monitor = Monitor::new(token.clone(), |progress| {
+        reported.push(progress);
+        token.cancel();
+    });
+    let mut stdin = vec![];
+    let result = exchange(&mut stdin, &mut &stdout[..], &Request::Items, &mut monitor);
+    drop(monitor);
+
+    assert_eq!(result, Err(crate::progress::CANCELLED.to_string()));
+    assert_eq!(reported.len(), 3);
+    // cancelled while the first progress was reported, sent once with the second one
+    assert_eq!(
+        String::from_utf8(stdin).unwrap(),
+        "{\"request\":\"items\"}\n{\"request\":\"cancel\"}\n"
+    );
+}
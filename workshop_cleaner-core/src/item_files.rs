@@ -0,0 +1,222 @@
+//! Offline details of an installed workshop item, read from its folder.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Keys of `mod.cpp` that point to images of the mod, in order of preference.
+const MOD_CPP_PICTURE_KEYS: [&str; 4] = ["picture", "logo", "logoOver", "logoSmall"];
+
+/// File or folder inside the item folder.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskEntry {
+    /// Path relative to the item folder.
+    pub path: PathBuf,
+    /// Bytes size on disk, folders include all their content.
+    pub size: u64,
+}
+
+/// Where the space of an item goes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ItemFiles {
+    /// Largest files at any depth, largest first.
+    pub largest_files: Vec<DiskEntry>,
+    /// Largest folders directly inside the item folder, largest first.
+    pub largest_folders: Vec<DiskEntry>,
+}
+
+/// Read sizes of files and folders of the item, at most `limit` of each are kept.
+pub fn read_item_files(item_dir: &Path, limit: usize) -> ItemFiles {
+    let mut files = vec![];
+    let mut folders = vec![];
+
+    for path in read_dir_paths(item_dir) {
+        if path.is_dir() {
+            let size = walk_dir(&path, item_dir, &mut files);
+            folders.push(relative_entry(&path, item_dir, size));
+        } else if let Ok(metadata) = path.metadata() {
+            files.push(relative_entry(&path, item_dir, metadata.len()));
+        }
+    }
+
+    ItemFiles {
+        largest_files: largest(files, limit),
+        largest_folders: largest(folders, limit),
+    }
+}
+
+/// Images that could show the item, existing files only, best candidates first.
+///
+/// Pictures named in `mod.cpp` come first, then `preview.*` files of the item folder.
+/// Files are not checked to be decodable, Arma pictures are often in its own `.paa` format.
+pub fn find_preview_images(item_dir: &Path) -> Vec<PathBuf> {
+    let mut images = vec![];
+
+    if let Ok(mod_cpp) = fs::read_to_string(item_dir.join("mod.cpp")) {
+        for picture in read_mod_cpp_pictures(&mod_cpp) {
+            // paths are usually relative to the mod folder, sometimes prefixed with its name
+            let picture = picture.replace('\\', "/");
+            let relative = Path::new(picture.trim_start_matches('/'));
+            let candidates = vec![
+                Some(item_dir.join(relative)),
+                relative.file_name().map(|name| item_dir.join(name)),
+            ];
+
+            if let Some(path) = candidates.into_iter().flatten().find(|p| p.is_file()) {
+                images.push(path);
+            }
+        }
+    }
+
+    let mut previews: Vec<PathBuf> = read_dir_paths(item_dir)
+        .into_iter()
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_stem()
+                .map_or(false, |stem| stem.eq_ignore_ascii_case("preview"))
+        })
+        .collect();
+    previews.sort();
+    images.extend(previews);
+
+    images.dedup();
+    images
+}
+
+/// Values of picture entries of `mod.cpp`, like `picture = "logo.paa";`.
+fn read_mod_cpp_pictures(mod_cpp: &str) -> Vec<String> {
+    let entries: Vec<(&str, &str)> = mod_cpp
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().trim_end_matches(';').trim().trim_matches('"');
+
+            Some((key.trim(), value))
+        })
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+
+    MOD_CPP_PICTURE_KEYS
+        .iter()
+        .filter_map(|key| {
+            entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.to_string())
+        })
+        .collect()
+}
+
+/// Add all files of the directory to `files`, returns total size of the directory.
+fn walk_dir(dir: &Path, root: &Path, files: &mut Vec<DiskEntry>) -> u64 {
+    let mut total = 0;
+
+    for path in read_dir_paths(dir) {
+        if path.is_dir() {
+            total += walk_dir(&path, root, files);
+        } else if let Ok(metadata) = path.metadata() {
+            total += metadata.len();
+            files.push(relative_entry(&path, root, metadata.len()));
+        }
+    }
+
+    total
+}
+
+fn read_dir_paths(dir: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|r| r.ok()).map(|r| r.path()).collect(),
+        Err(_) => vec![],
+    }
+}
+
+fn relative_entry(path: &Path, root: &Path, size: u64) -> DiskEntry {
+    DiskEntry {
+        path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+        size,
+    }
+}
+
+fn largest(mut entries: Vec<DiskEntry>, limit: usize) -> Vec<DiskEntry> {
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    entries.truncate(limit);
+
+    entries
+}
+
+#[test]
+fn test_read_mod_cpp_pictures() {
+    let mod_cpp = r#"
+        name = "Community Base Addons";
+        logo = "\x\cba\addons\main\logo_cba_ca.paa";
+        picture="logo_cba_ca.paa";
+        logoOver = "";
+        action = "https://github.com/CBATeam/CBA_A3";
+    "#;
+
+    assert_eq!(
+        read_mod_cpp_pictures(mod_cpp),
+        vec!["logo_cba_ca.paa", r"\x\cba\addons\main\logo_cba_ca.paa"]
+    );
+}
+
+#[test]
+fn test_find_preview_images() {
+    let dir = crate::test_support::test_dir("preview-images");
+    fs::write(dir.join("mod.cpp"), "picture = \"\\@mod\\logo.png\";\n").unwrap();
+    fs::write(dir.join("logo.png"), b"png").unwrap();
+    fs::write(dir.join("Preview.jpg"), b"jpg").unwrap();
+    fs::write(dir.join("readme.txt"), b"text").unwrap();
+
+    assert_eq!(
+        find_preview_images(&dir),
+        vec![dir.join("logo.png"), dir.join("Preview.jpg")]
+    );
+}
+
+#[test]
+fn test_read_item_files() {
+    let dir = crate::test_support::test_dir("item-files");
+    fs::create_dir_all(dir.join("addons").join("data")).unwrap();
+    fs::create_dir_all(dir.join("keys")).unwrap();
+    fs::write(dir.join("addons").join("main.pbo"), vec![0; 300]).unwrap();
+    fs::write(
+        dir.join("addons").join("data").join("sounds.pbo"),
+        vec![0; 500],
+    )
+    .unwrap();
+    fs::write(dir.join("keys").join("mod.bikey"), vec![0; 10]).unwrap();
+    fs::write(dir.join("mod.cpp"), vec![0; 20]).unwrap();
+
+    let files = read_item_files(&dir, 2);
+
+    assert_eq!(
+        files.largest_files,
+        vec![
+            DiskEntry {
+                path: Path::new("addons").join("data").join("sounds.pbo"),
+                size: 500
+            },
+            DiskEntry {
+                path: Path::new("addons").join("main.pbo"),
+                size: 300
+            },
+        ]
+    );
+    assert_eq!(
+        files.largest_folders,
+        vec![
+            DiskEntry {
+                path: PathBuf::from("addons"),
+                size: 800
+            },
+            DiskEntry {
+                path: PathBuf::from("keys"),
+                size: 10
+            },
+        ]
+    );
+}
@@ -6,12 +6,33 @@ pub use steamworks::{AppId, PublishedFileId};
 
 use crate::cleaner::WorkshopCleaner;
 
+pub mod appinfo;
 pub mod cleaner;
+pub mod disk_usage;
+pub mod helper;
+pub mod item_files;
 pub mod locator;
+pub mod manifest;
+pub mod progress;
+pub mod report;
+pub mod size_index;
+pub mod summary;
+#[cfg(test)]
+mod test_support;
+pub mod vdf;
 
+/// Environment variables Steam API reads the app ID from, before `steam_appid.txt`.
+const STEAM_APP_ID_VARS: [&str; 2] = ["SteamAppId", "SteamGameId"];
+
+/// Initialize Steam client of the app.
+///
+/// Helpers get the app ID in their environment, other processes write `steam_appid.txt` to
+/// the working directory.
 pub fn init(app_id: AppId) -> Result<WorkshopCleaner, String> {
-    std::fs::write("steam_appid.txt", format!("{}", app_id.0))
-        .expect("Failed to write steam_appid.txt");
+    if std::env::var(STEAM_APP_ID_VARS[0]).ok() != Some(app_id.0.to_string()) {
+        std::fs::write("steam_appid.txt", format!("{}", app_id.0))
+            .map_err(|e| format!("Could not write steam_appid.txt: {}", e))?;
+    }
 
     let client_result = Client::init();
 
@@ -1,6 +1,10 @@
+use std::collections::HashMap;
 use std::fs::{self};
+use std::path::PathBuf;
 
-use crate::AppId;
+use crate::cleaner::read_installed_items;
+use crate::manifest::AppManifest;
+use crate::{AppId, PublishedFileId};
 
 use crate::ToAppId;
 
@@ -8,37 +12,124 @@ const IGNORED_APP_IDS: &[u32] = &[
     241100, // Steam Controller configs
 ];
 
+/// App that has a workshop content directory in one of the Steam libraries.
+#[derive(Clone, Debug)]
+pub struct WorkshopApp {
+    pub app_id: AppId,
+    /// Name from local Steam files, `None` if neither app manifest nor app info cache know it.
+    pub name: Option<String>,
+    /// `steamapps` directory of the library containing the workshop content.
+    pub library: PathBuf,
+}
+
+impl WorkshopApp {
+    /// Workshop content directory of the app.
+    pub fn workshop_dir(&self) -> PathBuf {
+        self.library
+            .join("workshop")
+            .join("content")
+            .join(self.app_id.0.to_string())
+    }
+
+    /// Get workshop items installed in the library, regardless of subscription.
+    pub fn get_installed_items(&self) -> Vec<PublishedFileId> {
+        read_installed_items(&self.workshop_dir())
+    }
+}
+
 pub struct SteamLocator {
     steam: steamlocate::SteamDir,
 }
 
 impl SteamLocator {
     pub fn new() -> SteamLocator {
-        SteamLocator {
-            steam: steamlocate::SteamDir::locate().expect("Steam is not installed"),
+        Self::locate().expect("Could not locate Steam")
+    }
+
+    /// Locate Steam installation, errors if Steam is not installed.
+    pub fn locate() -> Result<SteamLocator, String> {
+        match steamlocate::SteamDir::locate() {
+            Some(steam) => Ok(SteamLocator { steam }),
+            None => Err("Steam is not installed".to_string()),
         }
     }
 
+    /// Use Steam installation at the path instead of the detected one.
+    pub fn at(path: PathBuf) -> Result<SteamLocator, String> {
+        if !path.join("steamapps").is_dir() {
+            return Err(format!("{} is not a Steam installation", path.display()));
+        }
+
+        let mut steam = steamlocate::SteamDir::default();
+        steam.path = path;
+
+        Ok(SteamLocator { steam })
+    }
+
     /// Get lists of Apps that are installed and have workshop directory in the steam library
-    pub fn get_installed_workshop_apps(&mut self) -> Vec<AppId> {
+    ///
+    /// Apps are named offline, from app manifest of installed apps or from the app info cache.
+    pub fn get_installed_workshop_apps(&mut self) -> Vec<WorkshopApp> {
         let libraries = &self.steam.libraryfolders().paths;
 
-        let workshop_apps: Vec<AppId> = libraries
+        let mut workshop_apps: Vec<WorkshopApp> = libraries
             .iter()
-            .filter_map(|p| fs::read_dir(format!("{}\\workshop\\content", p.to_str().unwrap())).ok())
-            .flat_map(|r| {
-                let app_ids: Vec<AppId> = r
+            .filter_map(|p| Some((p, fs::read_dir(p.join("workshop").join("content")).ok()?)))
+            .flat_map(|(library, r)| {
+                let apps: Vec<WorkshopApp> = r
                     .filter_map(|p| p.ok())
                     .filter_map(|d| d.path().to_app_id())
                     .filter(|a| !IGNORED_APP_IDS.contains(&a.0))
+                    .map(|app_id| WorkshopApp {
+                        app_id,
+                        name: AppManifest::read(library, app_id).ok().and_then(|m| m.name),
+                        library: library.clone(),
+                    })
                     .collect();
 
-                app_ids
+                apps
             })
             .collect();
 
+        let unnamed: Vec<AppId> = workshop_apps
+            .iter()
+            .filter(|a| a.name.is_none())
+            .map(|a| a.app_id)
+            .collect();
+        if !unnamed.is_empty() {
+            let mut names = self.get_cached_app_names(&unnamed);
+            for app in workshop_apps.iter_mut().filter(|a| a.name.is_none()) {
+                app.name = names.remove(&app.app_id.0);
+            }
+        }
+
         workshop_apps
     }
+
+    /// Find the app and install directory of a workshop item.
+    pub fn find_workshop_item(
+        &mut self,
+        item_id: PublishedFileId,
+    ) -> Option<(WorkshopApp, PathBuf)> {
+        self.get_installed_workshop_apps()
+            .into_iter()
+            .find_map(|app| {
+                let item_dir = app.workshop_dir().join(item_id.0.to_string());
+
+                if item_dir.is_dir() {
+                    Some((app, item_dir))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Get names of apps from the Steam client app info cache, works for uninstalled apps too.
+    pub fn get_cached_app_names(&self, app_ids: &[AppId]) -> HashMap<u32, String> {
+        let path = self.steam.path.join("appcache").join("appinfo.vdf");
+
+        crate::appinfo::read_names(&path, app_ids).unwrap_or_default()
+    }
 }
 
 #[test]
@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+
+use crate::{vdf, AppId, PublishedFileId};
+
+/// Installed app details from `steamapps/appmanifest_<id>.acf`.
+#[derive(Clone, Debug)]
+pub struct AppManifest {
+    pub app_id: AppId,
+    pub name: Option<String>,
+    pub install_dir: Option<String>,
+}
+
+impl AppManifest {
+    /// Path of the app manifest inside of library `steamapps` directory.
+    pub fn path(library: &Path, app_id: AppId) -> PathBuf {
+        library.join(format!("appmanifest_{}.acf", app_id.0))
+    }
+
+    /// Read manifest of the app from library `steamapps` directory.
+    pub fn read(library: &Path, app_id: AppId) -> Result<Self, String> {
+        let path = Self::path(library, app_id);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let (_, state) = vdf::parse(content)?;
+
+        let app_id = state
+            .get("appid")
+            .and_then(vdf::Vdf::as_str)
+            .and_then(|id| id.parse().ok())
+            .map(AppId)
+            .ok_or_else(|| "App manifest is missing appid".to_string())?;
+        let field = |key| state.get(key).and_then(vdf::Vdf::as_str).map(String::from);
+
+        Ok(AppManifest {
+            app_id,
+            name: field("name"),
+            install_dir: field("installdir"),
+        })
+    }
+}
+
+/// Workshop state of an app from `steamapps/workshop/appworkshop_<id>.acf`.
+#[derive(Clone, Debug)]
+pub struct AppWorkshopManifest {
+    pub app_id: AppId,
+    /// Items Steam tracks as installed.
+    pub installed_items: Vec<PublishedFileId>,
+    /// Items some account on this machine is subscribed to.
+    pub subscribed_items: Vec<PublishedFileId>,
+}
+
+impl AppWorkshopManifest {
+    /// Path of the workshop manifest inside of library `steamapps` directory.
+    pub fn path(library: &Path, app_id: AppId) -> PathBuf {
+        library
+            .join("workshop")
+            .join(format!("appworkshop_{}.acf", app_id.0))
+    }
+
+    /// Read workshop manifest of the app from library `steamapps` directory.
+    pub fn read(library: &Path, app_id: AppId) -> Result<Self, String> {
+        let path = Self::path(library, app_id);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let (_, workshop) = vdf::parse(content)?;
+
+        let app_id = workshop
+            .get("appid")
+            .and_then(vdf::Vdf::as_str)
+            .and_then(|id| id.parse().ok())
+            .map(AppId)
+            .ok_or_else(|| "Workshop manifest is missing appid".to_string())?;
+        let item_ids = |key, filter: &dyn Fn(&vdf::Vdf) -> bool| -> Vec<PublishedFileId> {
+            workshop
+                .get(key)
+                .map(vdf::Vdf::entries)
+                .unwrap_or_default()
+                .iter()
+                .filter(|(_, details)| filter(details))
+                .filter_map(|(id, _)| id.parse().ok().map(PublishedFileId))
+                .collect()
+        };
+
+        Ok(AppWorkshopManifest {
+            app_id,
+            installed_items: item_ids("WorkshopItemsInstalled", &|_| true),
+            subscribed_items: item_ids("WorkshopItemDetails", &|details| {
+                details
+                    .get("subscribedby")
+                    .and_then(vdf::Vdf::as_str)
+                    .map_or(false, |account| account != "0")
+            }),
+        })
+    }
+}
+
+#[test]
+fn test_parse_app_workshop_manifest() {
+    let manifest = AppWorkshopManifest::parse(
+        r#"
+"AppWorkshop"
+{
+	"appid"		"107410"
+	"WorkshopItemsInstalled"
+	{
+		"450814997"
+		{
+			"size"		"3145728"
+		}
+		"463939057"
+		{
+			"size"		"1024"
+		}
+	}
+	"WorkshopItemDetails"
+	{
+		"450814997"
+		{
+			"subscribedby"		"12345678"
+		}
+		"463939057"
+		{
+			"subscribedby"		"0"
+		}
+	}
+}
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(manifest.app_id, AppId(107410));
+    assert_eq!(
+        manifest.installed_items,
+        vec![PublishedFileId(450814997), PublishedFileId(463939057)]
+    );
+    assert_eq!(manifest.subscribed_items, vec![PublishedFileId(450814997)]);
+}
@@ -0,0 +1,152 @@
+//! Progress reporting and cancellation of long running operations.
+//!
+//! Operations that walk item directories or talk to Steam item by item take a `Monitor`. It
+//! reports what is going on to an observer and stops the operation between files once its
+//! `CancelToken` is cancelled.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Error of a cancelled operation.
+pub const CANCELLED: &str = "Cancelled";
+
+/// Minimal time between two `BytesCounted` reports of the same monitor.
+const COUNT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Step of an operation, `id` is the workshop item ID or `0` for other directories.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Progress {
+    /// Work on the item started, its state is read, its files are counted or removed.
+    ItemStarted {
+        id: u64,
+    },
+    /// Bytes and files of the item counted, or removed, so far.
+    BytesCounted {
+        id: u64,
+        bytes: u64,
+        files: u64,
+    },
+    /// All files of the item are counted.
+    ItemMeasured {
+        id: u64,
+        bytes: u64,
+        files: u64,
+    },
+    ItemRemoved {
+        id: u64,
+    },
+    ItemFailed {
+        id: u64,
+        error: String,
+    },
+}
+
+/// Shared flag that cancels operations monitored with it.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Observer and cancel token of a running operation.
+pub struct Monitor<'a> {
+    observer: Box<dyn FnMut(Progress) + 'a>,
+    token: CancelToken,
+    last_count: Option<Instant>,
+}
+
+impl<'a> Monitor<'a> {
+    pub fn new(token: CancelToken, observer: impl FnMut(Progress) + 'a) -> Self {
+        Monitor {
+            observer: Box::new(observer),
+            token,
+            last_count: None,
+        }
+    }
+
+    /// Monitor that ignores the progress and is never cancelled.
+    pub fn none() -> Self {
+        Self::new(CancelToken::new(), |_| ())
+    }
+
+    pub fn token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    /// Report the progress, `BytesCounted` is left out if the previous one was reported just now.
+    pub fn report(&mut self, progress: Progress) {
+        if let Progress::BytesCounted { .. } = progress {
+            let now = Instant::now();
+            if matches!(self.last_count, Some(last) if now - last < COUNT_INTERVAL) {
+                return;
+            }
+            self.last_count = Some(now);
+        }
+
+        (self.observer)(progress)
+    }
+
+    /// Fails with `CANCELLED` once the token is cancelled, called between files.
+    pub fn check(&self) -> Result<(), String> {
+        match self.token.is_cancelled() {
+            true => Err(CANCELLED.to_string()),
+            false => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_monitor() {
+    let mut reported = vec![];
+    let token = CancelToken::new();
+    let mut monitor = Monitor::new(token.clone(), |p| reported.push(p));
+
+    monitor.report(Progress::ItemStarted { id: 1 });
+    for files in 1..=3 {
+        monitor.report(Progress::BytesCounted {
+            id: 1,
+            bytes: files * 10,
+            files,
+        });
+    }
+    monitor.report(Progress::ItemRemoved { id: 1 });
+    assert_eq!(monitor.check(), Ok(()));
+    token.cancel();
+    assert_eq!(monitor.check(), Err(CANCELLED.to_string()));
+    drop(monitor);
+
+    // counts reported right after the first one are left out
+    assert_eq!(
+        reported,
+        [
+            Progress::ItemStarted { id: 1 },
+            Progress::BytesCounted {
+                id: 1,
+                bytes: 10,
+                files: 1
+            },
+            Progress::ItemRemoved { id: 1 },
+        ]
+    );
+}
@@ -0,0 +1,259 @@
+//! Machine-readable reports of scan and clean operations.
+
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cleaner::ItemState;
+
+/// Version of the report layout, bump when fields are renamed, removed or change meaning.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Single JSON document.
+    Json,
+    /// Comma separated values with a header row.
+    Csv,
+    /// One JSON document per item and line.
+    Ndjson,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "ndjson" => Ok(ReportFormat::Ndjson),
+            _ => Err(format!("Unknown report format {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub schema_version: u32,
+    /// Unix timestamp (seconds) of report creation.
+    pub generated_at: u64,
+    pub items: Vec<ReportItem>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReportItem {
+    pub id: u64,
+    pub title: Option<String>,
+    pub app_id: u32,
+    pub app_name: Option<String>,
+    pub path: PathBuf,
+    pub size_bytes: Option<u64>,
+    pub state: ItemState,
+    /// Result of the removal, `None` if removal was not attempted.
+    pub removal: Option<RemovalResult>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RemovalResult {
+    Removed,
+    Failed { reason: String },
+}
+
+/// NDJSON line, items carry schema version as there is no enclosing document.
+#[derive(Serialize)]
+struct NdjsonItem<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    item: &'a ReportItem,
+}
+
+/// Columns of CSV reports, written even when there are no items.
+pub const REPORT_CSV_HEADER: [&str; 10] = [
+    "schema_version",
+    "id",
+    "title",
+    "app_id",
+    "app_name",
+    "path",
+    "size_bytes",
+    "state",
+    "removal",
+    "removal_error",
+];
+
+/// CSV can not represent nested values, so the item is flattened, fields follow
+/// `REPORT_CSV_HEADER`.
+#[derive(Serialize)]
+struct CsvItem<'a> {
+    schema_version: u32,
+    id: u64,
+    title: Option<&'a str>,
+    app_id: u32,
+    app_name: Option<&'a str>,
+    path: String,
+    size_bytes: Option<u64>,
+    state: &'static str,
+    removal: Option<&'static str>,
+    removal_error: Option<&'a str>,
+}
+
+impl Report {
+    pub fn new(items: Vec<ReportItem>) -> Self {
+        Report {
+            schema_version: REPORT_SCHEMA_VERSION,
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            items,
+        }
+    }
+
+    pub fn write(&self, writer: impl Write, format: ReportFormat) -> io::Result<()> {
+        match format {
+            ReportFormat::Json => self.write_json(writer),
+            ReportFormat::Csv => self.write_csv(writer),
+            ReportFormat::Ndjson => self.write_ndjson(writer),
+        }
+    }
+
+    fn write_json(&self, mut writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)
+    }
+
+    fn write_ndjson(&self, mut writer: impl Write) -> io::Result<()> {
+        for item in &self.items {
+            let line = NdjsonItem {
+                schema_version: self.schema_version,
+                item,
+            };
+            serde_json::to_writer(&mut writer, &line)?;
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_csv(&self, writer: impl Write) -> io::Result<()> {
+        let mut csv = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        csv.write_record(REPORT_CSV_HEADER)?;
+
+        for item in &self.items {
+            let (removal, removal_error) = match &item.removal {
+                Some(RemovalResult::Removed) => (Some("removed"), None),
+                Some(RemovalResult::Failed { reason }) => (Some("failed"), Some(reason.as_str())),
+                None => (None, None),
+            };
+
+            csv.serialize(CsvItem {
+                schema_version: self.schema_version,
+                id: item.id,
+                title: item.title.as_deref(),
+                app_id: item.app_id,
+                app_name: item.app_name.as_deref(),
+                path: item.path.display().to_string(),
+                size_bytes: item.size_bytes,
+                state: item.state.as_str(),
+                removal,
+                removal_error,
+            })?;
+        }
+
+        csv.flush()
+    }
+}
+
+#[cfg(test)]
+fn test_report() -> Report {
+    let item = |id, state, removal| ReportItem {
+        id,
+        title: Some(format!("Item, \"{}\"", id)),
+        app_id: 107410,
+        app_name: Some("Arma 3".to_string()),
+        path: PathBuf::from(format!("/steam/workshop/content/107410/{}", id)),
+        size_bytes: Some(1024),
+        state,
+        removal,
+    };
+
+    Report::new(vec![
+        item(1, ItemState::Unsubscribed, Some(RemovalResult::Removed)),
+        item(
+            2,
+            ItemState::Unsubscribed,
+            Some(RemovalResult::Failed {
+                reason: "Access denied".to_string(),
+            }),
+        ),
+        item(3, ItemState::Pinned, None),
+    ])
+}
+
+#[test]
+fn test_report_json() {
+    let mut out = vec![];
+    test_report().write(&mut out, ReportFormat::Json).unwrap();
+
+    let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(value["schema_version"], REPORT_SCHEMA_VERSION);
+    assert_eq!(value["items"][0]["state"], "unsubscribed");
+    assert_eq!(value["items"][0]["removal"]["status"], "removed");
+    assert_eq!(value["items"][1]["removal"]["reason"], "Access denied");
+    assert!(value["items"][2]["removal"].is_null());
+
+    let report: Report = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report.items.len(), 3);
+}
+
+#[test]
+fn test_report_ndjson() {
+    let mut out = vec![];
+    test_report().write(&mut out, ReportFormat::Ndjson).unwrap();
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2]["schema_version"], REPORT_SCHEMA_VERSION);
+    assert_eq!(lines[2]["id"], 3);
+    assert_eq!(lines[2]["state"], "pinned");
+}
+
+#[test]
+fn test_report_csv() {
+    let mut out = vec![];
+    test_report().write(&mut out, ReportFormat::Csv).unwrap();
+
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        "schema_version,id,title,app_id,app_name,path,size_bytes,state,removal,removal_error"
+    );
+    assert_eq!(
+        lines[2],
+        "1,2,\"Item, \"\"2\"\"\",107410,Arma 3,/steam/workshop/content/107410/2,1024,unsubscribed,failed,Access denied"
+    );
+    assert_eq!(lines.len(), 4);
+
+    // report of a clean app still has the header
+    let mut out = vec![];
+    Report::new(vec![])
+        .write(&mut out, ReportFormat::Csv)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        format!("{}\n", REPORT_CSV_HEADER.join(","))
+    );
+}
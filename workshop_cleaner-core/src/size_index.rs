@@ -0,0 +1,295 @@
+//! Disk usage of items measured earlier, kept next to the metadata cache.
+//!
+//! An item is walked again only when its fingerprint changed: modification times and entry
+//! counts of its directories. Reading them is much cheaper than reading every file, so scans
+//! of large workshops start from the cached sizes.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    disk_usage::{DiskUsage, DiskUsageWalker},
+    progress::{Monitor, Progress},
+    AppId,
+};
+
+/// Version of the index file, files of other versions are ignored.
+const INDEX_VERSION: u32 = 2;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a, unlike the std hasher its output is the same with every Rust release.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Directories of an item with their modification times and entry counts, hashed by FNV-1a.
+///
+/// Directory symlinks are not followed, files changed in place don't change it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    dirs: u64,
+    entries: u64,
+    hash: u64,
+}
+
+impl Fingerprint {
+    /// Fingerprint of the directory, `None` if it does not exist or could not be read.
+    pub fn of(dir: &Path) -> Option<Self> {
+        let mut fingerprint = Fingerprint {
+            dirs: 0,
+            entries: 0,
+            hash: 0,
+        };
+        let mut dirs = vec![dir.to_path_buf()];
+
+        while let Some(current) = dirs.pop() {
+            let modified = fs::metadata(&current).ok()?.modified().ok()?;
+            let mut entries = 0u64;
+            for entry in fs::read_dir(&current).ok()? {
+                let entry = entry.ok()?;
+                entries += 1;
+                if entry.file_type().ok()?.is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+
+            // relative path with `/` separators, modification time and entry count, hashed with
+            // FNV-1a, directories are read in no particular order so their hashes are added up
+            let relative: Vec<String> = current
+                .strip_prefix(dir)
+                .unwrap_or(&current)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let modified = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+            let mut hash = fnv1a(FNV_OFFSET_BASIS, relative.join("/").as_bytes());
+            hash = fnv1a(hash, &modified.as_secs().to_le_bytes());
+            hash = fnv1a(hash, &modified.subsec_nanos().to_le_bytes());
+            hash = fnv1a(hash, &entries.to_le_bytes());
+
+            fingerprint.dirs += 1;
+            fingerprint.entries += entries;
+            fingerprint.hash = fingerprint.hash.wrapping_add(hash);
+        }
+
+        Some(fingerprint)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry {
+    fingerprint: Fingerprint,
+    /// Walker that measured the item, its mode and settings decide if the usage can be reused.
+    walker: DiskUsageWalker,
+    usage: DiskUsage,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    items: HashMap<u64, Entry>,
+}
+
+/// Disk usage of items of one app, by item ID.
+pub struct SizeIndex {
+    /// File the index is saved to, `None` keeps it in memory only.
+    path: Option<PathBuf>,
+    items: HashMap<u64, Entry>,
+}
+
+impl SizeIndex {
+    /// Index of the app in the cache directory, empty if there is none yet or it can't be read.
+    pub fn open(app_id: AppId) -> Self {
+        match dirs::cache_dir() {
+            Some(dir) => Self::at(
+                dir.join("workshop_cleaner")
+                    .join("sizes")
+                    .join(format!("{}.json", app_id.0)),
+            ),
+            None => SizeIndex {
+                path: None,
+                items: HashMap::new(),
+            },
+        }
+    }
+
+    /// Index saved to `path`, empty if there is none yet or it can't be read.
+    pub fn at(path: PathBuf) -> Self {
+        let items = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<IndexFile>(&data).ok())
+            .filter(|file| file.version == INDEX_VERSION)
+            .map(|file| file.items)
+            .unwrap_or_default();
+
+        SizeIndex {
+            path: Some(path),
+            items,
+        }
+    }
+
+    /// Same as `DiskUsageWalker::measure`, but only items with changed fingerprint are walked.
+    ///
+    /// Cached items are reported as measured right away. The index is saved if it changed.
+    pub fn measure(
+        &mut self,
+        walker: &DiskUsageWalker,
+        dirs: &[(u64, PathBuf)],
+        monitor: &mut Monitor,
+    ) -> Result<Vec<Option<DiskUsage>>, String> {
+        let mut usage = vec![None; dirs.len()];
+        let mut stale = vec![];
+        let mut changed = false;
+
+        for (index, (id, dir)) in dirs.iter().enumerate() {
+            monitor.check()?;
+
+            let fingerprint = match Fingerprint::of(dir) {
+                Some(fingerprint) => fingerprint,
+                None => {
+                    changed |= self.items.remove(id).is_some();
+                    continue;
+                }
+            };
+            match self.items.get(id) {
+                Some(entry) if entry.fingerprint == fingerprint && walker.reuses(&entry.walker) => {
+                    let mut cached = entry.usage.clone();
+                    walker.truncate(&mut cached);
+                    monitor.report(Progress::ItemMeasured {
+                        id: *id,
+                        bytes: cached.bytes(walker.mode()),
+                        files: cached.files,
+                    });
+                    usage[index] = Some(cached);
+                }
+                _ => stale.push((index, fingerprint)),
+            }
+        }
+
+        let stale_dirs: Vec<(u64, PathBuf)> = stale
+            .iter()
+            .map(|(index, _)| dirs[*index].clone())
+            .collect();
+        let measured = walker.measure(&stale_dirs, monitor)?;
+        for ((index, fingerprint), measured) in stale.into_iter().zip(measured) {
+            let id = dirs[index].0;
+            match &measured {
+                Some(measured) => {
+                    self.items.insert(
+                        id,
+                        Entry {
+                            fingerprint,
+                            walker: walker.clone(),
+                            usage: measured.clone(),
+                        },
+                    );
+                }
+                None => {
+                    self.items.remove(&id);
+                }
+            }
+            usage[index] = measured;
+            changed = true;
+        }
+
+        if changed {
+            // the index only saves time, measured usage is correct without it
+            let _ = self.save();
+        }
+
+        Ok(usage)
+    }
+
+    /// Forget the item, its directory was changed or removed, the index is saved.
+    pub fn invalidate(&mut self, id: u64) {
+        if self.items.remove(&id).is_some() {
+            let _ = self.save();
+        }
+    }
+
+    /// Write the index through a temporary file, so a crash leaves the previous one intact.
+    pub fn save(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let file = IndexFile {
+            version: INDEX_VERSION,
+            items: self.items.clone(),
+        };
+        let data = serde_json::to_vec(&file).map_err(|e| e.to_string())?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, data).map_err(|e| e.to_string())?;
+
+        fs::rename(&temp, path).map_err(|e| e.to_string())
+    }
+}
+
+#[test]
+fn test_fnv1a() {
+    // reference values of the 64-bit FNV-1a
+    assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+}
+
+#[test]
+fn test_size_index() {
+    let dir = crate::test_support::test_dir("size-index");
+    let item = dir.join("1");
+    fs::create_dir_all(item.join("addons")).unwrap();
+    fs::write(item.join("addons").join("main.pbo"), vec![0; 300]).unwrap();
+    let dirs = vec![(1, item.clone()), (2, dir.join("2"))];
+    let path = dir.join("index.json");
+    let walker = DiskUsageWalker::new();
+
+    // whether the item was walked, walked items are reported as started
+    let measure = |index: &mut SizeIndex| {
+        let mut walked = false;
+        let mut monitor = Monitor::new(Default::default(), |p| {
+            walked |= matches!(p, Progress::ItemStarted { .. })
+        });
+        let usage = index.measure(&walker, &dirs, &mut monitor).unwrap();
+        drop(monitor);
+
+        (
+            usage[0].as_ref().map(|u| (u.apparent_bytes, u.files)),
+            usage[1].is_none(),
+            walked,
+        )
+    };
+
+    let mut index = SizeIndex::at(path.clone());
+    assert_eq!(measure(&mut index), (Some((300, 1)), true, true));
+    assert_eq!(measure(&mut index), (Some((300, 1)), true, false));
+    assert_eq!(
+        measure(&mut SizeIndex::at(path.clone())),
+        (Some((300, 1)), true, false)
+    );
+
+    fs::write(item.join("addons").join("ui.pbo"), vec![0; 20]).unwrap();
+    assert_eq!(measure(&mut index), (Some((320, 2)), true, true));
+
+    index.invalidate(1);
+    let mut index = SizeIndex::at(path);
+    assert_eq!(measure(&mut index), (Some((320, 2)), true, true));
+
+    // usage of another mode is not reused
+    let allocated = DiskUsageWalker::new().with_mode(crate::disk_usage::SizeMode::Allocated);
+    assert!(!allocated.reuses(&walker));
+    assert!(DiskUsageWalker::new().with_largest_files(3).reuses(&walker));
+}
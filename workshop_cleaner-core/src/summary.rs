@@ -0,0 +1,168 @@
+//! Overview of workshop content of all installed apps, without initializing Steam for each of them.
+
+use crate::{
+    disk_usage::DiskUsageWalker,
+    locator::{SteamLocator, WorkshopApp},
+    manifest::AppWorkshopManifest,
+    progress::Monitor,
+    size_index::SizeIndex,
+    PublishedFileId,
+};
+
+#[derive(Clone, Debug)]
+pub struct AppSummary {
+    pub app: WorkshopApp,
+    /// Number of item directories in the workshop content directory.
+    pub installed_items: usize,
+    /// Installed items that no account on this machine is subscribed to.
+    ///
+    /// Unlike `ItemState::Unsubscribed` of a scan, items subscribed only by other accounts
+    /// are left out, so summary shows these as "unsubscribed by all".
+    pub orphaned_items: Vec<PublishedFileId>,
+    /// Bytes that would be freed by removing the orphaned items.
+    pub reclaimable_bytes: u64,
+}
+
+impl AppSummary {
+    /// Summarize workshop content of the app.
+    ///
+    /// Subscriptions are read from the app workshop manifest, which covers every account that
+    /// used this machine, so the result can differ from scan done for the current user only.
+    pub fn scan(app: WorkshopApp) -> Self {
+        let mut index = SizeIndex::open(app.app_id);
+
+        Self::scan_with_index(app, &mut index)
+    }
+
+    /// Same as `scan`, orphaned items are measured only if they changed since the index has them.
+    fn scan_with_index(app: WorkshopApp, index: &mut SizeIndex) -> Self {
+        let installed = app.get_installed_items();
+        let subscribed = AppWorkshopManifest::read(&app.library, app.app_id)
+            .map(|m| m.subscribed_items)
+            .unwrap_or_default();
+
+        let orphaned_items: Vec<PublishedFileId> = installed
+            .iter()
+            .filter(|i| !subscribed.contains(i))
+            .copied()
+            .collect();
+        let dirs: Vec<_> = orphaned_items
+            .iter()
+            .map(|i| (i.0, app.workshop_dir().join(i.0.to_string())))
+            .collect();
+        let reclaimable_bytes = index
+            .measure(&DiskUsageWalker::new(), &dirs, &mut Monitor::none())
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|usage| usage.apparent_bytes)
+            .sum();
+
+        AppSummary {
+            app,
+            installed_items: installed.len(),
+            orphaned_items,
+            reclaimable_bytes,
+        }
+    }
+}
+
+/// Summarize all apps with workshop content, the ones with most reclaimable space come first.
+pub fn scan_all_apps(locator: &mut SteamLocator) -> Vec<AppSummary> {
+    let mut summaries: Vec<AppSummary> = locator
+        .get_installed_workshop_apps()
+        .into_iter()
+        .map(AppSummary::scan)
+        .collect();
+    sort_summaries(&mut summaries);
+
+    summaries
+}
+
+/// Most reclaimable bytes first, then most orphaned items.
+fn sort_summaries(summaries: &mut [AppSummary]) {
+    summaries.sort_by(|a, b| {
+        b.reclaimable_bytes
+            .cmp(&a.reclaimable_bytes)
+            .then(b.orphaned_items.len().cmp(&a.orphaned_items.len()))
+    });
+}
+
+#[cfg(test)]
+fn test_summary(app_id: u32, orphaned_items: usize, reclaimable_bytes: u64) -> AppSummary {
+    AppSummary {
+        app: WorkshopApp {
+            app_id: crate::AppId(app_id),
+            name: None,
+            library: std::path::PathBuf::new(),
+        },
+        installed_items: orphaned_items,
+        orphaned_items: (0..orphaned_items as u64).map(PublishedFileId).collect(),
+        reclaimable_bytes,
+    }
+}
+
+#[test]
+fn test_app_summary_scan() {
+    use std::fs;
+
+    let library = crate::test_support::test_dir("summary");
+    let app = WorkshopApp {
+        app_id: crate::AppId(107410),
+        name: Some("Arma 3".to_string()),
+        library: library.to_path_buf(),
+    };
+    let content = app.workshop_dir();
+    for (id, size) in &[(1, 100), (2, 300), (3, 50)] {
+        fs::create_dir_all(content.join(id.to_string())).unwrap();
+        fs::write(content.join(id.to_string()).join("mod.pbo"), vec![0; *size]).unwrap();
+    }
+    fs::create_dir_all(content.join("backup")).unwrap();
+    fs::write(
+        library.join("workshop").join("appworkshop_107410.acf"),
+        r#"
+"AppWorkshop"
+{
+	"appid"		"107410"
+	"WorkshopItemDetails"
+	{
+		"1"
+		{
+			"subscribedby"		"12345678"
+		}
+		"3"
+		{
+			"subscribedby"		"0"
+		}
+	}
+}
+"#,
+    )
+    .unwrap();
+
+    let mut index = SizeIndex::at(library.join("sizes.json"));
+    let mut summary = AppSummary::scan_with_index(app, &mut index);
+    summary.orphaned_items.sort();
+
+    assert_eq!(summary.installed_items, 3);
+    assert_eq!(
+        summary.orphaned_items,
+        vec![PublishedFileId(2), PublishedFileId(3)]
+    );
+    assert_eq!(summary.reclaimable_bytes, 350);
+    assert!(library.join("sizes.json").is_file());
+}
+
+#[test]
+fn test_sort_summaries() {
+    let mut summaries = vec![
+        test_summary(1, 5, 100),
+        test_summary(2, 1, 900),
+        test_summary(3, 8, 100),
+        test_summary(4, 0, 0),
+    ];
+    sort_summaries(&mut summaries);
+
+    let order: Vec<u32> = summaries.iter().map(|s| s.app.app_id.0).collect();
+    assert_eq!(order, vec![2, 3, 1, 4]);
+}
@@ -0,0 +1,45 @@
+//! Fixtures shared by tests of several modules.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Directory of a test, unique to the test run and removed when dropped.
+pub struct TestDir(PathBuf);
+
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn test_dir(name: &str) -> TestDir {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let dir = std::env::temp_dir().join(format!(
+        "workshop_cleaner-{}-{}-{}",
+        name,
+        std::process::id(),
+        nanos
+    ));
+    fs::create_dir_all(&dir).unwrap();
+
+    TestDir(dir)
+}
@@ -0,0 +1,196 @@
+//! Reader for Valve's KeyValues (VDF) format, used by `.acf` manifests and other Steam files.
+
+use std::{iter::Peekable, str::Chars};
+
+/// Node of a KeyValues document.
+///
+/// Keys are matched case-insensitively, same as Steam does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vdf {
+    Value(String),
+    Table(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+    /// Get child node of a table.
+    pub fn get(&self, key: &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Table(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            Vdf::Value(_) => None,
+        }
+    }
+
+    /// Get nested node, following `path` of table keys.
+    pub fn get_path(&self, path: &[&str]) -> Option<&Vdf> {
+        path.iter().try_fold(self, |node, key| node.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Vdf::Value(v) => Some(v),
+            Vdf::Table(_) => None,
+        }
+    }
+
+    /// Entries of a table, empty for values.
+    pub fn entries(&self) -> &[(String, Vdf)] {
+        match self {
+            Vdf::Table(entries) => entries,
+            Vdf::Value(_) => &[],
+        }
+    }
+}
+
+/// Parse text KeyValues document, returns the root key and its node.
+pub fn parse(input: &str) -> Result<(String, Vdf), String> {
+    let mut tokens = Tokenizer {
+        chars: input.chars().peekable(),
+    };
+
+    let key = match tokens.next()? {
+        Some(Token::Str(key)) => key,
+        _ => return Err("Expected root key".to_string()),
+    };
+    let value = parse_value(&mut tokens)?;
+
+    Ok((key, value))
+}
+
+fn parse_value(tokens: &mut Tokenizer) -> Result<Vdf, String> {
+    match tokens.next()? {
+        Some(Token::Str(value)) => Ok(Vdf::Value(value)),
+        Some(Token::Open) => parse_table(tokens),
+        Some(Token::Close) => Err("Unexpected '}'".to_string()),
+        None => Err("Unexpected end of input".to_string()),
+    }
+}
+
+fn parse_table(tokens: &mut Tokenizer) -> Result<Vdf, String> {
+    let mut entries = vec![];
+
+    loop {
+        match tokens.next()? {
+            Some(Token::Str(key)) => entries.push((key, parse_value(tokens)?)),
+            Some(Token::Close) => return Ok(Vdf::Table(entries)),
+            Some(Token::Open) => return Err("Unexpected '{'".to_string()),
+            None => return Err("Unclosed table".to_string()),
+        }
+    }
+}
+
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Tokenizer<'_> {
+    fn next(&mut self) -> Result<Option<Token>, String> {
+        loop {
+            let c = match self.chars.next() {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+
+            match c {
+                c if c.is_whitespace() => continue,
+                '/' if self.chars.peek() == Some(&'/') => self.skip_line(),
+                // platform conditionals, e.g. `[$WIN32]`, are not evaluated
+                '[' => self.skip_until(']'),
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(|s| Some(Token::Str(s))),
+                c => return Ok(Some(Token::Str(self.unquoted(c)))),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let mut s = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some(c) => s.push(c),
+                    None => break,
+                },
+                Some(c) => s.push(c),
+                None => break,
+            }
+        }
+
+        Err("Unterminated string".to_string())
+    }
+
+    fn unquoted(&mut self, first: char) -> String {
+        let mut s = first.to_string();
+
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '"' || c == '{' || c == '}' {
+                break;
+            }
+            s.push(c);
+            self.chars.next();
+        }
+
+        s
+    }
+
+    fn skip_line(&mut self) {
+        self.skip_until('\n');
+    }
+
+    fn skip_until(&mut self, end: char) {
+        for c in self.chars.by_ref() {
+            if c == end {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_manifest() {
+    let (key, manifest) = parse(
+        r#"
+"AppState"
+{
+	"appid"		"107410"
+	"name"		"Arma 3"
+	// comment
+	"InstalledDepots"
+	{
+		"107411"
+		{
+			"manifest"		"2871540937496418562"
+		}
+	}
+	"escaped"	"a \"b\" c\\d"
+}
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(key, "AppState");
+    assert_eq!(manifest.get("Name").and_then(Vdf::as_str), Some("Arma 3"));
+    assert_eq!(
+        manifest
+            .get_path(&["InstalledDepots", "107411", "manifest"])
+            .and_then(Vdf::as_str),
+        Some("2871540937496418562")
+    );
+    assert_eq!(
+        manifest.get("escaped").and_then(Vdf::as_str),
+        Some(r#"a "b" c\d"#)
+    );
+}
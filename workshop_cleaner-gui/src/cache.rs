@@ -0,0 +1,26 @@
+//! Metadata cache of the GUI, keeps preview images of workshop items so they can be shown offline.
+
+use std::{fs, path::PathBuf, time::Duration};
+
+fn preview_cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("workshop_cleaner").join("previews"))
+}
+
+/// Preview image of the item downloaded earlier, if there is one younger than `ttl`.
+pub fn cached_preview(item_id: u64, ttl: Duration) -> Option<PathBuf> {
+    let path = preview_cache_dir()?.join(item_id.to_string());
+    let age = path.metadata().ok()?.modified().ok()?.elapsed().ok()?;
+
+    (age < ttl).then_some(path)
+}
+
+/// Save downloaded preview image of the item, returns path of the cached file.
+pub fn store_preview(item_id: u64, image: &[u8]) -> Result<PathBuf, String> {
+    let dir = preview_cache_dir().ok_or("Could not find cache directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let path = dir.join(item_id.to_string());
+    fs::write(&path, image).map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
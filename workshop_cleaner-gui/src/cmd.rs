@@ -1,9 +1,48 @@
+use std::{any::Any, path::PathBuf, sync::Arc};
+
 use druid::im::Vector;
-use druid::Selector;
+use druid::{ExtEventSink, ImageBuf, Selector, Target};
+use workshop_cleaner_core::item_files::ItemFiles;
+use workshop_cleaner_session::Event;
+
+use crate::data::{Dialog, Notification, SteamApp, SteamWorkshopItem};
 
-use crate::data::SteamApp;
+/// Event of the session, sent from its thread to the delegate.
+pub const SESSION_EVENT: Selector<Event> = Selector::new("session.event");
 
-pub const SET_STEAM_APPS: Selector<Vector<SteamApp>> = Selector::new("data.set_steam_apps");
+/// Find apps of the Steam installation at the path, or of the detected one.
+pub const LOCATE_APPS: Selector<Option<PathBuf>> = Selector::new("data.locate_apps");
+
+/// Save settings to the disk, widgets get it after the delegate to react to changed settings.
+pub const SAVE_SETTINGS: Selector = Selector::new("data.save_settings");
 
 pub const SELECT_STEAM_APP: Selector<SteamApp> = Selector::new("data.select_steam_app");
-pub const CONFIRM_STEAM_APP: Selector = Selector::new("data.confirm_steam_app");
+
+pub const SHOW_ITEM_DETAIL: Selector<SteamWorkshopItem> = Selector::new("data.show_item_detail");
+pub const CLOSE_ITEM_DETAIL: Selector = Selector::new("data.close_item_detail");
+/// Detail commands carry path of the item folder, so results of a previous item can be ignored.
+pub const SET_DETAIL_PREVIEW: Selector<(String, Option<Arc<ImageBuf>>)> =
+    Selector::new("data.set_detail_preview");
+pub const SET_DETAIL_FILES: Selector<(String, ItemFiles)> = Selector::new("data.set_detail_files");
+
+pub const REQUEST_REMOVE_ITEMS: Selector<Vector<SteamWorkshopItem>> =
+    Selector::new("data.request_remove_items");
+pub const CONFIRM_REMOVE_ITEMS: Selector<Vector<SteamWorkshopItem>> =
+    Selector::new("data.confirm_remove_items");
+pub const CANCEL_REMOVAL: Selector = Selector::new("data.cancel_removal");
+
+pub const OPEN_DIALOG: Selector<Dialog> = Selector::new("dialog.open");
+pub const UPDATE_DIALOG: Selector<Dialog> = Selector::new("dialog.update");
+pub const CLOSE_DIALOG: Selector<u64> = Selector::new("dialog.close");
+/// Confirm or dismiss the dialog currently shown.
+pub const CONFIRM_DIALOG: Selector = Selector::new("dialog.confirm");
+pub const DISMISS_DIALOG: Selector = Selector::new("dialog.dismiss");
+
+/// Show a banner and add it to the log.
+pub const NOTIFY: Selector<Notification> = Selector::new("notification.notify");
+pub const DISMISS_NOTIFICATION: Selector<u64> = Selector::new("notification.dismiss");
+
+/// Submit command from a background thread, it's dropped if the app was closed meanwhile.
+pub fn submit<T: Any + Send>(sink: &ExtEventSink, selector: Selector<T>, payload: T) {
+    let _ = sink.submit_command(selector, Box::new(payload), Target::Auto);
+}
@@ -1,43 +1,43 @@
-use druid::im::{vector, Vector};
-use druid::{widget::Controller, Target, Widget};
-use workshop_cleaner_core::locator::SteamLocator;
+use std::path::PathBuf;
 
-use crate::steam;
-use crate::{
-    cmd,
-    data::{AppState, SteamApp},
-};
+use druid::{widget::Controller, Widget};
 
-pub struct MainController {}
+use crate::{cmd, data::AppState};
+
+pub struct MainController {
+    /// Steam root setting the apps list was scanned with.
+    steam_root: Option<PathBuf>,
+}
 
 impl MainController {
     pub fn new() -> Self {
-        MainController {}
+        MainController { steam_root: None }
     }
 
     fn on_window_connected(
-        &self,
-        ctx: &druid::EventCtx,
+        &mut self,
+        ctx: &mut druid::EventCtx,
         _event: &druid::Event,
         data: &mut AppState,
         _env: &druid::Env,
     ) {
-        // Initialize apps list
-        let sink = ctx.get_external_handle();
-        std::thread::spawn(move || {
-            let apps: Vector<SteamApp> = SteamLocator::new()
-                .get_installed_workshop_apps()
-                .iter()
-                .map(|a| SteamApp {
-                    app_id: a.0,
-                    name: steam::get_app_item_name(a).unwrap_or("Unkown".to_string()),
-                    workshop_items: vector!(),
-                })
-                .collect();
-
-            sink.submit_command(cmd::SET_STEAM_APPS, apps, Target::Auto)
-                .expect("Failed to send command");
-        });
+        self.scan_apps(ctx, data);
+    }
+
+    /// Settings were saved, apps of another Steam installation are scanned if the root changed.
+    fn on_settings_saved(&mut self, ctx: &mut druid::EventCtx, data: &mut AppState) {
+        if data.settings.steam_root_path() != self.steam_root && !data.is_removal_running() {
+            data.apps = None;
+            data.selected_app = None;
+            self.scan_apps(ctx, data);
+        }
+    }
+
+    /// Initialize apps list, sorted by reclaimable space
+    fn scan_apps(&mut self, ctx: &mut druid::EventCtx, data: &AppState) {
+        self.steam_root = data.settings.steam_root_path();
+
+        ctx.submit_command(cmd::LOCATE_APPS.with(self.steam_root.clone()));
     }
 }
 
@@ -55,6 +55,13 @@ where
     ) {
         match event {
             druid::Event::WindowConnected => self.on_window_connected(ctx, event, data, env),
+            druid::Event::WindowSize(size) => {
+                data.settings.window_width = size.width;
+                data.settings.window_height = size.height;
+            }
+            druid::Event::Command(cmd) if cmd.is(cmd::SAVE_SETTINGS) => {
+                self.on_settings_saved(ctx, data)
+            }
             _ => (),
         };
 
@@ -1,2 +1,5 @@
 mod main;
 pub use main::MainController;
+
+mod split;
+pub use split::SplitPointController;
@@ -0,0 +1,35 @@
+use druid::{widget::Controller, Event, Widget};
+
+use crate::data::AppState;
+
+/// Keeps position of the dragged split bar in the settings, so it's restored on the next start.
+///
+/// `Split` does not expose its split point, the controller follows the mouse while it drags.
+pub struct SplitPointController;
+
+impl<W> Controller<AppState, W> for SplitPointController
+where
+    W: Widget<AppState>,
+{
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut druid::EventCtx,
+        event: &Event,
+        data: &mut AppState,
+        env: &druid::Env,
+    ) {
+        let dragging = ctx.is_active();
+        child.event(ctx, event, data, env);
+
+        match event {
+            Event::MouseMove(mouse) | Event::MouseUp(mouse) if dragging => {
+                let width = ctx.size().width;
+                if width > 0.0 {
+                    data.settings.split_point = (mouse.pos.x / width).max(0.0).min(1.0);
+                }
+            }
+            _ => (),
+        }
+    }
+}
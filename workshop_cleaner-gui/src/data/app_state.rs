@@ -1,14 +1,146 @@
 use druid::im::{vector, Vector};
 use druid::{Data, Lens};
 
-use super::SteamApp;
+use super::{
+    DialogQueue, ItemDetail, ItemDetails, ItemFilter, Notifications, RemovalState, ScanState,
+    Settings, SteamApp, SteamWorkshopItem,
+};
 
 #[derive(Clone, Debug, Data, Lens)]
 pub struct AppState {
     pub apps: Option<Vector<SteamApp>>,
-    pub items: Vector<super::SteamWorkshopItem>,
+    /// Changed through the methods below, they keep the visible items in sync.
+    pub items: Vector<SteamWorkshopItem>,
     pub selected_app: Option<SteamApp>,
-    pub selected_app_confirmed: bool,
+    pub scan: ScanState,
+    /// Kept while switching apps.
+    pub item_filter: ItemFilter,
+    /// Indexes in `items` of the items passing the filter, in the selected order.
+    pub visible: Vector<usize>,
+    /// Items at `visible`, shown by the items list and the treemap.
+    pub visible_items: Vector<SteamWorkshopItem>,
+    pub dialogs: DialogQueue,
+    pub notifications: Notifications,
+    pub settings: Settings,
+    /// Settings screen is shown instead of the items.
+    pub settings_open: bool,
+    /// Item shown in the detail pane.
+    pub detail: Option<ItemDetail>,
+    /// Finished and total number of items of the running removal job.
+    pub removal_progress: Option<(usize, usize)>,
+}
+
+impl AppState {
+    pub fn set_items(&mut self, items: Vector<SteamWorkshopItem>) {
+        self.items = items;
+        self.refresh_visible();
+    }
+
+    /// Filter and sort the items again, after the items or the filter changed.
+    pub fn refresh_visible(&mut self) {
+        self.visible = self.item_filter.apply(&self.items).into_iter().collect();
+        self.visible_items = self
+            .visible
+            .iter()
+            .map(|i| self.items[*i].clone())
+            .collect();
+    }
+
+    /// Current values of the item opened in the detail pane.
+    pub fn detail_item(&self) -> Option<SteamWorkshopItem> {
+        let detail = self.detail.as_ref()?;
+
+        self.items
+            .iter()
+            .find(|i| i.is_same_item(&detail.item))
+            .cloned()
+    }
+
+    pub fn select_all_items(&mut self) {
+        for i in self.visible.iter() {
+            let item = &mut self.items[*i];
+            item.selected = item.is_removable();
+        }
+        self.refresh_visible();
+    }
+
+    pub fn select_no_items(&mut self) {
+        for item in self.items.iter_mut() {
+            item.selected = false;
+        }
+        self.refresh_visible();
+    }
+
+    pub fn invert_item_selection(&mut self) {
+        for i in self.visible.iter() {
+            let item = &mut self.items[*i];
+            item.selected = !item.selected && item.is_removable();
+        }
+        self.refresh_visible();
+    }
+
+    /// Selected items that can be removed, items hidden by the filter are left out.
+    pub fn selected_items(&self) -> Vector<SteamWorkshopItem> {
+        self.items
+            .iter()
+            .filter(|i| i.selected && i.is_removable() && self.item_filter.matches(i))
+            .cloned()
+            .collect()
+    }
+
+    /// Total bytes size of selected items, items of unknown size are not counted.
+    pub fn selected_size(&self) -> u64 {
+        self.selected_items().iter().filter_map(|i| i.size).sum()
+    }
+
+    pub fn is_removal_running(&self) -> bool {
+        self.removal_progress.is_some()
+    }
+
+    pub fn set_item_size(&mut self, item_id: u64, size: Option<u64>) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == item_id) {
+            item.size = size;
+            self.refresh_visible();
+        }
+    }
+
+    pub fn set_item_details(&mut self, item_id: u64, details: ItemDetails) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == item_id) {
+            item.title = Some(details.title);
+            item.author = details.author;
+            item.description = Some(details.description).filter(|d| !d.is_empty());
+            item.preview_url = Some(details.preview_url).filter(|u| !u.is_empty());
+            item.time_updated = Some(details.time_updated);
+            self.refresh_visible();
+        }
+    }
+
+    /// Update removal state of the item, finished items count towards the job progress.
+    pub fn set_removal_state(&mut self, item_id: u64, state: RemovalState) {
+        let finished = matches!(state, RemovalState::Removed | RemovalState::Failed(_));
+
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == item_id) {
+            item.selected = item.selected && state != RemovalState::Removed;
+            item.removal = state;
+            self.refresh_visible();
+        }
+        if let (true, Some((done, _))) = (finished, self.removal_progress.as_mut()) {
+            *done += 1;
+        }
+    }
+
+    /// End the removal job, items it did not get to are left installed.
+    pub fn finish_removal(&mut self) {
+        for item in self
+            .items
+            .iter_mut()
+            .filter(|i| i.removal == RemovalState::Queued)
+        {
+            item.removal = RemovalState::Installed;
+        }
+        self.removal_progress = None;
+        self.refresh_visible();
+    }
 }
 
 impl Default for AppState {
@@ -17,7 +149,114 @@ impl Default for AppState {
             apps: None,
             items: vector![],
             selected_app: None,
-            selected_app_confirmed: false,
+            scan: ScanState::Done,
+            item_filter: ItemFilter::default(),
+            visible: vector![],
+            visible_items: vector![],
+            dialogs: DialogQueue::default(),
+            notifications: Notifications::default(),
+            settings: Settings::default(),
+            settings_open: false,
+            detail: None,
+            removal_progress: None,
+        }
+    }
+}
+
+/// Lens to the items passing the filter, changes of the items are written back to `AppState.items`.
+pub struct VisibleItems;
+
+impl Lens<AppState, Vector<SteamWorkshopItem>> for VisibleItems {
+    fn with<V, F: FnOnce(&Vector<SteamWorkshopItem>) -> V>(&self, data: &AppState, f: F) -> V {
+        f(&data.visible_items)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut Vector<SteamWorkshopItem>) -> V>(
+        &self,
+        data: &mut AppState,
+        f: F,
+    ) -> V {
+        let mut visible = data.visible_items.clone();
+        let result = f(&mut visible);
+
+        if !visible.same(&data.visible_items) {
+            // widgets change fields of the items like the selection, rows keep their item
+            for (row, index) in data.visible.iter().enumerate() {
+                if let (Some(changed), Some(item)) = (visible.get(row), data.items.get_mut(*index))
+                {
+                    if !item.same(changed) {
+                        *item = changed.clone();
+                    }
+                }
+            }
+            data.visible_items = visible;
         }
+
+        result
     }
 }
+
+/// Lens to the item filter, the visible items are filtered again when it changes.
+pub struct VisibleItemsFilter;
+
+impl Lens<AppState, ItemFilter> for VisibleItemsFilter {
+    fn with<V, F: FnOnce(&ItemFilter) -> V>(&self, data: &AppState, f: F) -> V {
+        f(&data.item_filter)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut ItemFilter) -> V>(&self, data: &mut AppState, f: F) -> V {
+        let mut filter = data.item_filter.clone();
+        let result = f(&mut filter);
+
+        if !filter.same(&data.item_filter) {
+            data.item_filter = filter;
+            data.refresh_visible();
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+fn test_item(id: u64, size: u64) -> SteamWorkshopItem {
+    let mut item = SteamWorkshopItem::from(&workshop_cleaner_session::Item::new(
+        id,
+        workshop_cleaner_core::cleaner::ItemState::Unsubscribed,
+    ));
+    item.size = Some(size);
+
+    item
+}
+
+#[test]
+fn test_visible_items() {
+    let mut state = AppState::default();
+    state.set_items(vector![
+        test_item(1, 10),
+        test_item(2, 30),
+        test_item(3, 20)
+    ]);
+    let ids = |state: &AppState| -> Vec<u64> {
+        VisibleItems.with(state, |items| items.iter().map(|i| i.id).collect())
+    };
+    assert_eq!(ids(&state), vec![2, 3, 1]);
+
+    VisibleItemsFilter.with_mut(&mut state, |filter| {
+        filter.min_size_mb = 15.0 / 1024.0 / 1024.0
+    });
+    assert_eq!(ids(&state), vec![2, 3]);
+
+    state.set_item_size(1, Some(40));
+    assert_eq!(ids(&state), vec![1, 2, 3]);
+
+    // changes of the visible rows are written to the item at the same index
+    VisibleItems.with_mut(&mut state, |items| items[1].selected = true);
+    let selected: Vec<u64> = state
+        .items
+        .iter()
+        .filter(|i| i.selected)
+        .map(|i| i.id)
+        .collect();
+    assert_eq!(selected, vec![2]);
+    assert!(state.visible_items[1].selected);
+}
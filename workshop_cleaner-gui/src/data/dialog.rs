@@ -0,0 +1,192 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use druid::im::{vector, Vector};
+use druid::{Command, Data, Lens};
+
+static NEXT_DIALOG_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone, Copy, Debug, Data, PartialEq)]
+pub enum DialogKind {
+    /// Asks the user to confirm an action, Enter confirms and Escape dismisses it.
+    Confirm,
+    /// Reports a failure, Enter and Escape close it.
+    Error,
+    /// Shows progress of a background job, stays open until it's closed by its id.
+    Progress,
+}
+
+#[derive(Clone, Debug, Data)]
+pub struct Dialog {
+    pub id: u64,
+    pub kind: DialogKind,
+    pub title: String,
+    pub message: String,
+    /// Finished and total steps of a progress dialog.
+    pub progress: Option<(usize, usize)>,
+    /// Submitted when the dialog is confirmed.
+    #[data(ignore)]
+    pub on_confirm: Option<Command>,
+    /// Submitted when the dialog is dismissed, for progress dialogs it's the cancel action.
+    #[data(ignore)]
+    pub on_dismiss: Option<Command>,
+}
+
+impl Dialog {
+    fn new(kind: DialogKind, title: impl Into<String>, message: impl Into<String>) -> Self {
+        Dialog {
+            id: NEXT_DIALOG_ID.fetch_add(1, Ordering::Relaxed),
+            kind,
+            title: title.into(),
+            message: message.into(),
+            progress: None,
+            on_confirm: None,
+            on_dismiss: None,
+        }
+    }
+
+    pub fn confirm(
+        title: impl Into<String>,
+        message: impl Into<String>,
+        on_confirm: impl Into<Command>,
+    ) -> Self {
+        Dialog {
+            on_confirm: Some(on_confirm.into()),
+            ..Self::new(DialogKind::Confirm, title, message)
+        }
+    }
+
+    pub fn error(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(DialogKind::Error, title, message)
+    }
+
+    pub fn progress(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(DialogKind::Progress, title, message)
+    }
+
+    /// Builder style method for setting the dismiss action.
+    ///
+    /// Progress dialogs with a dismiss action can be cancelled by the user.
+    pub fn with_on_dismiss(mut self, on_dismiss: impl Into<Command>) -> Self {
+        self.on_dismiss = Some(on_dismiss.into());
+
+        self
+    }
+}
+
+/// Dialogs waiting to be shown, only the first one is visible.
+#[derive(Clone, Debug, Data, Lens)]
+pub struct DialogQueue {
+    pub dialogs: Vector<Dialog>,
+}
+
+impl DialogQueue {
+    pub fn current(&self) -> Option<&Dialog> {
+        self.dialogs.front()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dialogs.is_empty()
+    }
+
+    pub fn open(&mut self, dialog: Dialog) {
+        self.dialogs.push_back(dialog);
+    }
+
+    /// Replace the dialog of the same id, if it's still open.
+    pub fn update(&mut self, dialog: Dialog) {
+        if let Some(open) = self.dialogs.iter_mut().find(|d| d.id == dialog.id) {
+            *open = dialog;
+        }
+    }
+
+    /// Update message and progress of an open dialog.
+    pub fn set_progress(&mut self, id: u64, message: impl Into<String>, progress: (usize, usize)) {
+        if let Some(open) = self.dialogs.iter_mut().find(|d| d.id == id) {
+            open.message = message.into();
+            open.progress = Some(progress);
+        }
+    }
+
+    pub fn close(&mut self, id: u64) {
+        self.dialogs.retain(|d| d.id != id);
+    }
+
+    /// Confirm the current dialog, returns command that should be submitted.
+    ///
+    /// Progress dialogs can't be confirmed.
+    pub fn confirm(&mut self) -> Option<Command> {
+        match self.current()?.kind {
+            DialogKind::Confirm | DialogKind::Error => self.dialogs.pop_front()?.on_confirm,
+            DialogKind::Progress => None,
+        }
+    }
+
+    /// Dismiss the current dialog, returns command that should be submitted.
+    ///
+    /// Progress dialogs stay open, their dismiss action is expected to finish the job.
+    pub fn dismiss(&mut self) -> Option<Command> {
+        match self.current()?.kind {
+            DialogKind::Confirm | DialogKind::Error => self.dialogs.pop_front()?.on_dismiss,
+            DialogKind::Progress => self.current()?.on_dismiss.clone(),
+        }
+    }
+}
+
+impl Default for DialogQueue {
+    fn default() -> Self {
+        DialogQueue { dialogs: vector![] }
+    }
+}
+
+#[cfg(test)]
+const TEST_CONFIRMED: druid::Selector = druid::Selector::new("test.confirmed");
+#[cfg(test)]
+const TEST_DISMISSED: druid::Selector = druid::Selector::new("test.dismissed");
+
+#[test]
+fn test_dialog_queue_order() {
+    let mut queue = DialogQueue::default();
+    let first = Dialog::confirm("Remove", "Remove item?", TEST_CONFIRMED);
+    let second = Dialog::error("Steam", "Could not connect to Steam");
+    let second_id = second.id;
+
+    queue.open(first);
+    queue.open(second);
+
+    assert!(queue.confirm().unwrap().is(TEST_CONFIRMED));
+    assert_eq!(queue.current().unwrap().id, second_id);
+    assert!(queue.dismiss().is_none());
+    assert!(queue.is_empty());
+    assert!(queue.confirm().is_none());
+}
+
+#[test]
+fn test_dialog_queue_dismiss() {
+    let mut queue = DialogQueue::default();
+    queue.open(
+        Dialog::confirm("Remove", "Remove item?", TEST_CONFIRMED).with_on_dismiss(TEST_DISMISSED),
+    );
+
+    assert!(queue.dismiss().unwrap().is(TEST_DISMISSED));
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_dialog_queue_progress() {
+    let mut queue = DialogQueue::default();
+    let progress = Dialog::progress("Removing", "Starting...").with_on_dismiss(TEST_DISMISSED);
+    let id = progress.id;
+    queue.open(progress);
+
+    // progress dialog is closed by its owner only
+    assert!(queue.confirm().is_none());
+    assert!(queue.dismiss().unwrap().is(TEST_DISMISSED));
+    assert_eq!(queue.current().unwrap().id, id);
+
+    queue.set_progress(id, "Removing 2 of 3...", (1, 3));
+    assert_eq!(queue.current().unwrap().progress, Some((1, 3)));
+    assert_eq!(queue.current().unwrap().message, "Removing 2 of 3...");
+
+    queue.close(id);
+    assert!(queue.is_empty());
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use druid::im::Vector;
+use druid::{Data, ImageBuf, Lens};
+use workshop_cleaner_core::item_files::DiskEntry;
+
+use super::SteamWorkshopItem;
+
+/// File or folder of the item shown in the detail pane.
+#[derive(Clone, Debug, Data, Lens)]
+pub struct FileEntry {
+    /// Path relative to the item folder.
+    pub path: String,
+    pub size: u64,
+}
+
+impl From<&DiskEntry> for FileEntry {
+    fn from(entry: &DiskEntry) -> Self {
+        FileEntry {
+            path: entry.path.display().to_string(),
+            size: entry.size,
+        }
+    }
+}
+
+/// Item opened in the detail pane, parts read from the disk are `None` until they are loaded.
+#[derive(Clone, Debug, Data, Lens)]
+pub struct ItemDetail {
+    /// Opened item, current values are in `AppState.items`.
+    pub item: SteamWorkshopItem,
+    /// Folder of the item on the disk.
+    pub path: String,
+    pub preview: Option<Arc<ImageBuf>>,
+    /// No preview image was found.
+    pub preview_missing: bool,
+    pub largest_files: Option<Vector<FileEntry>>,
+    pub largest_folders: Option<Vector<FileEntry>>,
+}
+
+impl ItemDetail {
+    pub fn new(item: SteamWorkshopItem, path: String) -> Self {
+        ItemDetail {
+            item,
+            path,
+            preview: None,
+            preview_missing: false,
+            largest_files: None,
+            largest_folders: None,
+        }
+    }
+}
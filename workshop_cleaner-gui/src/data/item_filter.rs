@@ -0,0 +1,200 @@
+use druid::im::Vector;
+use druid::{Data, Lens};
+use workshop_cleaner_core::cleaner::ItemState;
+
+use super::SteamWorkshopItem;
+
+/// Search, filter and sort settings of the items list.
+#[derive(Clone, Debug, Data, Lens)]
+pub struct ItemFilter {
+    /// Case insensitive text matched against item title and ID.
+    pub search: String,
+    pub show_unsubscribed: bool,
+    pub show_pinned: bool,
+    pub show_in_use: bool,
+    pub show_unknown_folders: bool,
+    /// Items smaller than this are hidden, items of unknown size are always shown.
+    pub min_size_mb: f64,
+    pub sort: ItemSort,
+}
+
+#[derive(Clone, Copy, Debug, Data, PartialEq)]
+pub enum ItemSort {
+    /// Largest first.
+    Size,
+    Title,
+    Id,
+    /// Most recently updated first.
+    Updated,
+}
+
+impl ItemFilter {
+    pub fn matches(&self, item: &SteamWorkshopItem) -> bool {
+        let state_shown = match item.state {
+            ItemState::Unsubscribed => self.show_unsubscribed,
+            ItemState::Pinned => self.show_pinned,
+            ItemState::InUse => self.show_in_use,
+            ItemState::UnknownFolder => self.show_unknown_folders,
+        };
+        let large_enough = item.size.map_or(true, |size| {
+            size as f64 >= self.min_size_mb * 1024.0 * 1024.0
+        });
+
+        state_shown && large_enough && self.matches_search(item)
+    }
+
+    fn matches_search(&self, item: &SteamWorkshopItem) -> bool {
+        let search = self.search.trim().to_lowercase();
+        if search.is_empty() {
+            return true;
+        }
+
+        item.to_string().to_lowercase().contains(&search)
+    }
+
+    /// Indexes of the filtered items in the selected order.
+    pub fn apply(&self, items: &Vector<SteamWorkshopItem>) -> Vec<usize> {
+        let mut indexes: Vec<usize> = (0..items.len())
+            .filter(|i| self.matches(&items[*i]))
+            .collect();
+
+        match self.sort {
+            ItemSort::Size => indexes.sort_by(|a, b| items[*b].size.cmp(&items[*a].size)),
+            ItemSort::Title => indexes.sort_by_cached_key(|i| items[*i].to_string().to_lowercase()),
+            ItemSort::Id => indexes.sort_by_key(|i| (items[*i].id, items[*i].folder.clone())),
+            ItemSort::Updated => {
+                indexes.sort_by(|a, b| items[*b].time_updated.cmp(&items[*a].time_updated))
+            }
+        }
+
+        indexes
+    }
+}
+
+impl Default for ItemFilter {
+    fn default() -> Self {
+        ItemFilter {
+            search: String::new(),
+            show_unsubscribed: true,
+            show_pinned: true,
+            show_in_use: true,
+            show_unknown_folders: true,
+            min_size_mb: 0.0,
+            sort: ItemSort::Size,
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_item(id: u64, state: ItemState, size: Option<u64>) -> SteamWorkshopItem {
+    let mut item = SteamWorkshopItem::from(&workshop_cleaner_session::Item::new(id, state));
+    item.size = size;
+
+    item
+}
+
+#[test]
+fn test_item_filter_states() {
+    let unsubscribed = test_item(1, ItemState::Unsubscribed, None);
+    let pinned = test_item(2, ItemState::Pinned, None);
+    let in_use = test_item(3, ItemState::InUse, None);
+    let mut unknown = test_item(0, ItemState::UnknownFolder, None);
+    unknown.folder = Some("backup".to_string());
+
+    let filter = ItemFilter {
+        show_pinned: false,
+        show_unknown_folders: false,
+        ..ItemFilter::default()
+    };
+    assert!(filter.matches(&unsubscribed));
+    assert!(!filter.matches(&pinned));
+    assert!(filter.matches(&in_use));
+    assert!(!filter.matches(&unknown));
+
+    let filter = ItemFilter {
+        show_unsubscribed: false,
+        show_in_use: false,
+        ..ItemFilter::default()
+    };
+    assert!(!filter.matches(&unsubscribed));
+    assert!(filter.matches(&pinned));
+    assert!(!filter.matches(&in_use));
+    assert!(filter.matches(&unknown));
+}
+
+#[test]
+fn test_item_filter_min_size() {
+    let filter = ItemFilter {
+        min_size_mb: 2.0,
+        ..ItemFilter::default()
+    };
+
+    assert!(!filter.matches(&test_item(1, ItemState::Unsubscribed, Some(1024 * 1024))));
+    assert!(filter.matches(&test_item(
+        2,
+        ItemState::Unsubscribed,
+        Some(2 * 1024 * 1024)
+    )));
+    assert!(filter.matches(&test_item(3, ItemState::Unsubscribed, None)));
+}
+
+#[test]
+fn test_item_filter_search() {
+    let mut item = test_item(123456, ItemState::Unsubscribed, None);
+    item.title = Some("CBA_A3".to_string());
+    let search = |text: &str| ItemFilter {
+        search: text.to_string(),
+        ..ItemFilter::default()
+    };
+
+    assert!(search("").matches(&item));
+    assert!(search("  cba ").matches(&item));
+    assert!(search("Cba_a3").matches(&item));
+    assert!(search("3456").matches(&item));
+    assert!(!search("ace").matches(&item));
+}
+
+#[test]
+fn test_item_filter_sort() {
+    let mut items = Vector::new();
+    for (id, title, size, updated) in [
+        (3, "beta", Some(10), Some(300)),
+        (1, "Alpha", None, Some(100)),
+        (2, "gamma", Some(30), None),
+        (4, "delta", Some(20), Some(200)),
+    ] {
+        let mut item = test_item(id, ItemState::Unsubscribed, size);
+        item.title = Some(title.to_string());
+        item.time_updated = updated;
+        items.push_back(item);
+    }
+    let ids = |sort: ItemSort| -> Vec<u64> {
+        let filter = ItemFilter {
+            sort,
+            ..ItemFilter::default()
+        };
+        filter
+            .apply(&items)
+            .into_iter()
+            .map(|i| items[i].id)
+            .collect()
+    };
+
+    assert_eq!(ids(ItemSort::Size), vec![2, 4, 3, 1]);
+    assert_eq!(ids(ItemSort::Title), vec![1, 3, 4, 2]);
+    assert_eq!(ids(ItemSort::Id), vec![1, 2, 3, 4]);
+    assert_eq!(ids(ItemSort::Updated), vec![3, 4, 1, 2]);
+
+    // "beta" is too small, "gamma" and "Alpha" don't match the search
+    let hidden = ItemFilter {
+        search: "E".to_string(),
+        min_size_mb: 15.0 / 1024.0 / 1024.0,
+        ..ItemFilter::default()
+    };
+    let visible: Vec<u64> = hidden
+        .apply(&items)
+        .into_iter()
+        .map(|i| items[i].id)
+        .collect();
+    assert_eq!(visible, vec![4]);
+}
@@ -1,6 +1,34 @@
 mod app_state;
 pub use app_state::AppState;
+pub use app_state::VisibleItems;
+pub use app_state::VisibleItemsFilter;
+
+mod dialog;
+pub use dialog::Dialog;
+pub use dialog::DialogKind;
+pub use dialog::DialogQueue;
+
+mod item_detail;
+pub use item_detail::FileEntry;
+pub use item_detail::ItemDetail;
+
+mod item_filter;
+pub use item_filter::ItemFilter;
+pub use item_filter::ItemSort;
+
+mod notification;
+pub use notification::Notification;
+pub use notification::Notifications;
+pub use notification::Severity;
+
+mod settings;
+pub use settings::RemovalStrategy;
+pub use settings::Settings;
+pub use settings::ThemeMode;
 
 mod steam;
+pub use steam::RemovalState;
+pub use steam::ScanState;
 pub use steam::SteamApp;
 pub use steam::SteamWorkshopItem;
+pub use workshop_cleaner_session::ItemDetails;
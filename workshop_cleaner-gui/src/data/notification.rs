@@ -0,0 +1,126 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use druid::im::{vector, Vector};
+use druid::{Data, Lens};
+
+static NEXT_NOTIFICATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Number of banners shown at once, older ones are still in the log.
+const MAX_TOASTS: usize = 3;
+/// Number of events kept in the log.
+const MAX_LOG: usize = 200;
+
+#[derive(Clone, Copy, Debug, Data, PartialEq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Debug, Data, Lens)]
+pub struct Notification {
+    pub id: u64,
+    pub severity: Severity,
+    pub message: String,
+    /// Unix timestamp (seconds) of the event.
+    pub time: i64,
+}
+
+impl Notification {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Notification {
+            id: NEXT_NOTIFICATION_ID.fetch_add(1, Ordering::Relaxed),
+            severity,
+            message: message.into(),
+            time: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(Severity::Info, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+}
+
+/// Banners waiting to be dismissed and the log of recent events.
+#[derive(Clone, Debug, Data, Lens)]
+pub struct Notifications {
+    pub toasts: Vector<Notification>,
+    /// Oldest first.
+    pub log: Vector<Notification>,
+    pub log_open: bool,
+}
+
+impl Notifications {
+    /// Show the notification as a banner and add it to the log.
+    pub fn push(&mut self, notification: Notification) {
+        self.toasts.push_back(notification.clone());
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+
+        self.record(notification);
+    }
+
+    /// Add the notification to the log only, for events the user already sees elsewhere.
+    pub fn record(&mut self, notification: Notification) {
+        self.log.push_back(notification);
+        while self.log.len() > MAX_LOG {
+            self.log.pop_front();
+        }
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|n| n.id != id);
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Notifications {
+            toasts: vector![],
+            log: vector![],
+            log_open: false,
+        }
+    }
+}
+
+#[test]
+fn test_notifications_push_and_dismiss() {
+    let mut notifications = Notifications::default();
+    let first = Notification::error("Could not connect to Steam");
+    let first_id = first.id;
+
+    notifications.push(first);
+    notifications.push(Notification::info("Removed 2 items"));
+    notifications.dismiss(first_id);
+
+    assert_eq!(notifications.toasts.len(), 1);
+    assert_eq!(notifications.toasts[0].message, "Removed 2 items");
+    assert_eq!(notifications.log.len(), 2);
+}
+
+#[test]
+fn test_notifications_limits() {
+    let mut notifications = Notifications::default();
+
+    for i in 0..MAX_LOG + 1 {
+        notifications.push(Notification::warning(format!("Warning {}", i)));
+    }
+    notifications.record(Notification::info("Scanned"));
+
+    assert_eq!(notifications.toasts.len(), MAX_TOASTS);
+    assert_eq!(
+        notifications.toasts[0].message,
+        format!("Warning {}", MAX_LOG - 2)
+    );
+    assert_eq!(notifications.log.len(), MAX_LOG);
+    assert_eq!(notifications.log.back().unwrap().message, "Scanned");
+}
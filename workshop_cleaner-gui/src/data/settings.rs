@@ -0,0 +1,143 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use druid::{Data, Lens};
+use serde::{Deserialize, Serialize};
+use workshop_cleaner_core::cleaner;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// What happens to folders of removed items.
+#[derive(Clone, Copy, Debug, Data, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalStrategy {
+    Delete,
+    /// Folders are moved into `Settings.removal_folder`.
+    MoveToFolder,
+}
+
+/// Color theme of the GUI.
+#[derive(Clone, Copy, Debug, Data, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    /// Light or dark as the system prefers, dark if it's unknown.
+    System,
+    Light,
+    Dark,
+}
+
+/// User settings and window state, kept in the platform config directory between runs.
+#[derive(Clone, Debug, Data, Lens, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window_width: f64,
+    pub window_height: f64,
+    /// Position of the bar between apps and items, as a fraction of the window width.
+    pub split_point: f64,
+    pub last_app_id: Option<u32>,
+    /// Steam installation used instead of the detected one, empty to detect it.
+    pub steam_root: String,
+    pub unsubscribe_on_delete: bool,
+    pub removal_strategy: RemovalStrategy,
+    pub removal_folder: String,
+    /// Days a cached preview image is used before it's downloaded again.
+    pub metadata_cache_ttl_days: u64,
+    pub theme: ThemeMode,
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join("workshop_cleaner")
+                .join(SETTINGS_FILE),
+        )
+    }
+
+    /// Read saved settings, defaults are used if there are none or they could not be read.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Could not find config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| e.to_string())
+    }
+
+    pub fn steam_root_path(&self) -> Option<PathBuf> {
+        match self.steam_root.trim() {
+            "" => None,
+            root => Some(PathBuf::from(root)),
+        }
+    }
+
+    /// Removal strategy for the core, errors if the folder to move items into is not set.
+    pub fn cleaner_removal_strategy(&self) -> Result<cleaner::RemovalStrategy, String> {
+        match (self.removal_strategy, self.removal_folder.trim()) {
+            (RemovalStrategy::Delete, _) => Ok(cleaner::RemovalStrategy::Delete),
+            (RemovalStrategy::MoveToFolder, "") => {
+                Err("Choose a folder for removed items in the settings".to_string())
+            }
+            (RemovalStrategy::MoveToFolder, folder) => {
+                Ok(cleaner::RemovalStrategy::MoveTo(PathBuf::from(folder)))
+            }
+        }
+    }
+
+    pub fn metadata_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.metadata_cache_ttl_days * 24 * 60 * 60)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window_width: 1000.0,
+            window_height: 512.0,
+            split_point: 0.3,
+            last_app_id: None,
+            steam_root: String::new(),
+            unsubscribe_on_delete: true,
+            removal_strategy: RemovalStrategy::Delete,
+            removal_folder: String::new(),
+            metadata_cache_ttl_days: 30,
+            theme: ThemeMode::System,
+        }
+    }
+}
+
+#[test]
+fn test_settings_partial_file() {
+    let settings: Settings = serde_json::from_str(
+        r#"{"window_width": 1280.0, "removal_strategy": "move_to_folder", "theme": "light"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(settings.window_width, 1280.0);
+    assert_eq!(settings.window_height, Settings::default().window_height);
+    assert_eq!(settings.removal_strategy, RemovalStrategy::MoveToFolder);
+    assert_eq!(settings.theme, ThemeMode::Light);
+}
+
+#[test]
+fn test_settings_roundtrip() {
+    let settings = Settings {
+        last_app_id: Some(107410),
+        steam_root: "/opt/steam".to_string(),
+        ..Settings::default()
+    };
+    let json = serde_json::to_string(&settings).unwrap();
+
+    assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+    assert_eq!(
+        settings.steam_root_path(),
+        Some(PathBuf::from("/opt/steam"))
+    );
+}
@@ -1,13 +1,32 @@
 use std::fmt;
 
-use druid::im::Vector;
+use druid::im::{vector, Vector};
 use druid::{Data, Lens};
+use workshop_cleaner_core::cleaner::ItemState;
+use workshop_cleaner_session::{self as session, App, Item};
 
 #[derive(Clone, Debug, Data, Lens)]
 pub struct SteamApp {
     pub app_id: u32,
     pub name: String,
     pub workshop_items: Vector<super::SteamWorkshopItem>,
+    pub installed_items: usize,
+    /// Installed items no account on this machine is subscribed to.
+    pub orphaned_items: usize,
+    pub reclaimable_bytes: u64,
+}
+
+impl From<&App> for SteamApp {
+    fn from(app: &App) -> Self {
+        SteamApp {
+            app_id: app.app_id,
+            name: app.name.clone(),
+            workshop_items: vector![],
+            installed_items: app.installed_items,
+            orphaned_items: app.orphaned_items,
+            reclaimable_bytes: app.reclaimable_bytes,
+        }
+    }
 }
 
 impl fmt::Display for SteamApp {
@@ -16,11 +35,107 @@ impl fmt::Display for SteamApp {
     }
 }
 
-#[derive(Clone, Debug, Data)]
-pub struct SteamWorkshopItem(pub u64);
+#[derive(Clone, Debug, Data, Lens)]
+pub struct SteamWorkshopItem {
+    /// Workshop item ID, `0` for unknown folders.
+    pub id: u64,
+    /// Name of a folder that is not a workshop item.
+    pub folder: Option<String>,
+    /// Details below are loaded in the background, `None` until they arrive or if unknown.
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub preview_url: Option<String>,
+    /// Bytes size on disk.
+    pub size: Option<u64>,
+    /// Unix timestamp (seconds) of the last update in the workshop.
+    pub time_updated: Option<u64>,
+    #[data(same_fn = "PartialEq::eq")]
+    pub state: ItemState,
+    pub removal: RemovalState,
+    pub selected: bool,
+}
+
+impl SteamWorkshopItem {
+    /// Both values describe the same item or folder, regardless of their other fields.
+    pub fn is_same_item(&self, other: &SteamWorkshopItem) -> bool {
+        self.id == other.id && self.folder == other.folder
+    }
+
+    /// Name of the item folder in the workshop content directory.
+    pub fn dir_name(&self) -> String {
+        match &self.folder {
+            Some(folder) => folder.clone(),
+            None => self.id.to_string(),
+        }
+    }
+
+    /// Item is not used by Steam, still on the disk and no removal is in progress.
+    pub fn is_removable(&self) -> bool {
+        self.state == ItemState::Unsubscribed
+            && matches!(
+                self.removal,
+                RemovalState::Installed | RemovalState::Failed(_)
+            )
+    }
+}
+
+impl From<&Item> for SteamWorkshopItem {
+    fn from(item: &Item) -> Self {
+        SteamWorkshopItem {
+            id: item.id,
+            folder: item.folder.clone(),
+            title: item.title.clone(),
+            author: item.author.clone(),
+            description: item.description.clone(),
+            preview_url: item.preview_url.clone(),
+            size: item.size,
+            time_updated: item.time_updated,
+            state: item.state,
+            removal: item.removal.clone().into(),
+            selected: false,
+        }
+    }
+}
 
 impl fmt::Display for SteamWorkshopItem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        match (&self.folder, &self.title) {
+            (Some(folder), _) => write!(f, "{}", folder),
+            (None, Some(title)) => write!(f, "{} ({})", title, self.id),
+            (None, None) => write!(f, "{}", self.id),
+        }
     }
 }
+
+/// Progress of the workshop item removal, as `session::RemovalState` but comparable by druid.
+#[derive(Clone, Debug, Data, PartialEq)]
+pub enum RemovalState {
+    Installed,
+    /// Waiting for other items of the same removal job.
+    Queued,
+    Removing,
+    Removed,
+    Failed(String),
+}
+
+impl From<session::RemovalState> for RemovalState {
+    fn from(state: session::RemovalState) -> Self {
+        match state {
+            session::RemovalState::Installed => RemovalState::Installed,
+            session::RemovalState::Queued => RemovalState::Queued,
+            session::RemovalState::Removing => RemovalState::Removing,
+            session::RemovalState::Removed => RemovalState::Removed,
+            session::RemovalState::Failed(e) => RemovalState::Failed(e),
+        }
+    }
+}
+
+/// Progress of the workshop scan of the selected app.
+#[derive(Clone, Debug, Data, PartialEq)]
+pub enum ScanState {
+    /// Scan is running, with description of the current step.
+    Scanning(String),
+    Failed(String),
+    Done,
+}
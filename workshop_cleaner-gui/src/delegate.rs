@@ -1,18 +1,228 @@
-use druid::{AppDelegate, Handled};
-use workshop_cleaner_core::AppId;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use druid::im::{vector, Vector};
+use druid::{AppDelegate, Event, ExtEventSink, Handled, ImageBuf, KbKey};
+use workshop_cleaner_core::{
+    item_files::{find_preview_images, read_item_files},
+    progress::Progress,
+};
+use workshop_cleaner_session::{
+    self as session, Command, RemovalSummary, Session, SessionHandle, SteamBackend,
+};
+use workshop_cleaner_web::SteamWebClient;
 
 use crate::{
-    cmd as commands,
-    data::{AppState, SteamWorkshopItem},
+    cache, cmd as commands,
+    data::{
+        AppState, Dialog, FileEntry, ItemDetail, Notification, RemovalState, ScanState, SteamApp,
+        SteamWorkshopItem,
+    },
+    format_size,
 };
 
+/// Number of largest files and folders shown in the detail pane.
+const DETAIL_FILES_LIMIT: usize = 10;
+
 pub struct Delegate {
-    cleaner: Option<workshop_cleaner_core::cleaner::WorkshopCleaner>,
+    /// Session running the workflow, its events come back as `SESSION_EVENT` commands.
+    session: SessionHandle,
+    /// Workshop content directory of the scanned app.
+    workshop_dir: Option<PathBuf>,
+    sink: ExtEventSink,
+    web: SteamWebClient,
+    /// Progress dialog of the running removal job.
+    removal_dialog: Option<u64>,
 }
 
 impl Delegate {
-    pub fn new() -> Self {
-        Delegate { cleaner: None }
+    pub fn new(sink: ExtEventSink) -> Self {
+        let web = SteamWebClient::new();
+        let backend = SteamBackend::new().with_web_client(web.clone());
+        let events = sink.clone();
+        let session = Session::new(backend, move |event| {
+            commands::submit(&events, commands::SESSION_EVENT, event)
+        });
+
+        Delegate {
+            session: session.spawn(),
+            workshop_dir: None,
+            sink,
+            web,
+            removal_dialog: None,
+        }
+    }
+
+    /// Load preview image and largest files of the item for the detail pane on a background thread.
+    ///
+    /// Preview is looked up offline first, in the metadata cache and then in the item folder.
+    /// Only if neither has one it's downloaded from the workshop and kept in the cache.
+    fn load_item_detail(&self, item: &SteamWorkshopItem, item_dir: PathBuf, cache_ttl: Duration) {
+        let sink = self.sink.clone();
+        let web = self.web.clone();
+        let item_id = item.id;
+        let preview_url = item.preview_url.clone().filter(|_| item_id != 0);
+        let key = item_dir.display().to_string();
+
+        std::thread::spawn(move || {
+            let cached = match item_id {
+                0 => None,
+                id => cache::cached_preview(id, cache_ttl),
+            };
+            let mut preview = cached
+                .into_iter()
+                .chain(find_preview_images(&item_dir))
+                .find_map(|path| load_image(&path));
+
+            if let (None, Some(url)) = (&preview, preview_url) {
+                if let Ok(image) = web.download(&url) {
+                    let _ = cache::store_preview(item_id, &image);
+                    preview = ImageBuf::from_data(&image).ok();
+                }
+            }
+            commands::submit(
+                &sink,
+                commands::SET_DETAIL_PREVIEW,
+                (key.clone(), preview.map(Arc::new)),
+            );
+
+            let files = read_item_files(&item_dir, DETAIL_FILES_LIMIT);
+            commands::submit(&sink, commands::SET_DETAIL_FILES, (key, files));
+        });
+    }
+
+    /// Show the session event in the app state.
+    fn session_event(
+        &mut self,
+        ctx: &mut druid::DelegateCtx,
+        event: &session::Event,
+        data: &mut AppState,
+    ) {
+        match event {
+            session::Event::AppsLocated(apps) => {
+                let apps: Vector<SteamApp> = apps.iter().map(SteamApp::from).collect();
+                let last_app = data
+                    .settings
+                    .last_app_id
+                    .and_then(|id| apps.iter().find(|a| a.app_id == id).cloned());
+
+                data.selected_app = None;
+                data.apps = Some(apps);
+                if let Some(app) = last_app {
+                    ctx.submit_command(commands::SELECT_STEAM_APP.with(app));
+                }
+            }
+            session::Event::AppsFailed(e) => {
+                data.selected_app = None;
+                data.apps = Some(vector![]);
+                data.notifications
+                    .record(Notification::error(format!("Could not find Steam: {}", e)));
+                data.dialogs
+                    .open(Dialog::error("Could not find Steam", e.clone()));
+            }
+            session::Event::ScanProgress { app_id, text } => {
+                if is_selected_app(data, *app_id) {
+                    data.scan = ScanState::Scanning(text.clone());
+                }
+            }
+            session::Event::ScanFailed { app_id, error } => {
+                if is_selected_app(data, *app_id) {
+                    data.notifications
+                        .record(Notification::error(error.clone()));
+                    data.dialogs
+                        .open(Dialog::error("Scan failed", error.clone()));
+                    data.scan = ScanState::Failed(error.clone());
+                }
+            }
+            session::Event::ScanFinished {
+                app_id,
+                items,
+                workshop_dir,
+            } => {
+                if !is_selected_app(data, *app_id) {
+                    return;
+                }
+
+                self.workshop_dir = workshop_dir.clone();
+                data.set_items(items.iter().map(SteamWorkshopItem::from).collect());
+                data.scan = ScanState::Done;
+                if let Some(app) = &data.selected_app {
+                    let message = format!("Found {} installed items of {}", data.items.len(), app);
+                    data.notifications.record(Notification::info(message));
+                }
+            }
+            session::Event::ItemDetails(item_id, details) => {
+                data.set_item_details(*item_id, details.clone())
+            }
+            session::Event::ItemSize(item_id, size) => data.set_item_size(*item_id, *size),
+            session::Event::DetailsFailed(e) | session::Event::SizesFailed(e) => {
+                data.notifications.push(Notification::warning(e.clone()))
+            }
+            session::Event::ItemRemoval(item) => {
+                if let session::RemovalState::Failed(e) = &item.removal {
+                    data.notifications.record(Notification::error(format!(
+                        "Could not remove item {}: {}",
+                        item.id, e
+                    )));
+                }
+                data.set_removal_state(item.id, item.removal.clone().into());
+
+                if let (Some(id), Some((done, total))) =
+                    (self.removal_dialog, data.removal_progress)
+                {
+                    let message = match done < total {
+                        true => format!("Removing {} of {}...", done + 1, total),
+                        false => "Finishing...".to_string(),
+                    };
+                    data.dialogs.set_progress(id, message, (done, total));
+                }
+            }
+            session::Event::RemovalFailed(e) => {
+                self.finish_removal(data);
+                data.dialogs
+                    .open(Dialog::error("Could not remove items", e.clone()));
+            }
+            session::Event::RemovalFinished(summary) => {
+                self.finish_removal(data);
+                data.notifications.push(removal_notification(summary));
+            }
+            session::Event::Progress(progress) => self.show_progress(data, progress),
+            session::Event::StateChanged(_) => (),
+        }
+    }
+
+    /// Show progress of the scan next to its spinner and of the removal in its dialog.
+    fn show_progress(&self, data: &mut AppState, progress: &Progress) {
+        match progress {
+            Progress::ItemStarted { id } if matches!(data.scan, ScanState::Scanning(_)) => {
+                data.scan = ScanState::Scanning(format!("Reading state of item {}...", id));
+            }
+            Progress::BytesCounted { bytes, files, .. } => {
+                if let (Some(id), Some((done, total))) =
+                    (self.removal_dialog, data.removal_progress)
+                {
+                    let message = format!(
+                        "Removing {} of {}, deleted {} files ({})...",
+                        done + 1,
+                        total,
+                        files,
+                        format_size(Some(*bytes))
+                    );
+                    data.dialogs.set_progress(id, message, (done, total));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn finish_removal(&mut self, data: &mut AppState) {
+        if let Some(id) = self.removal_dialog.take() {
+            data.dialogs.close(id);
+        }
+        data.finish_removal();
     }
 }
 
@@ -25,47 +235,269 @@ impl AppDelegate<AppState> for Delegate {
         data: &mut AppState,
         env: &druid::Env,
     ) -> druid::Handled {
-        if let Some(apps) = cmd.get(commands::SET_STEAM_APPS).cloned() {
-            data.selected_app = None;
-            data.apps = Some(apps);
+        if let Some(event) = cmd.get(commands::SESSION_EVENT) {
+            self.session_event(ctx, event, data);
 
             return Handled::Yes;
         }
 
-        if let Some(app) = cmd.get(commands::SELECT_STEAM_APP).cloned().as_mut() {
-            if !data.selected_app_confirmed {
-                data.selected_app = Some(app.clone());
+        if let Some(steam_root) = cmd.get(commands::LOCATE_APPS).cloned() {
+            self.workshop_dir = None;
+            self.session.cancel();
+            self.session.send(Command::LocateApps { steam_root });
+
+            return Handled::Yes;
+        }
+
+        if cmd.is(commands::SAVE_SETTINGS) {
+            match data.settings.save() {
+                Ok(_) => data
+                    .notifications
+                    .record(Notification::info("Settings saved")),
+                Err(e) => data.notifications.push(Notification::error(format!(
+                    "Could not save settings: {}",
+                    e
+                ))),
+            }
+
+            // main controller rescans apps if Steam root changed
+            return Handled::No;
+        }
+
+        if let Some(app) = cmd.get(commands::SELECT_STEAM_APP).cloned() {
+            // removal job needs Steam client of the current app until it finishes
+            if data.is_removal_running() {
+                return Handled::Yes;
             }
 
+            data.selected_app = Some(app.clone());
+            data.settings.last_app_id = Some(app.app_id);
+            data.settings_open = false;
+            data.set_items(vector![]);
+            data.detail = None;
+            data.scan = ScanState::Scanning("Starting scan...".to_string());
+            // loading of the previous app is cancelled, the session shuts its helper down
+            self.workshop_dir = None;
+            self.session.cancel();
+            self.session.send(Command::SelectApp(app.app_id));
+
             return Handled::Yes;
         }
 
-        if cmd.is(commands::CONFIRM_STEAM_APP) {
-            data.selected_app_confirmed = true;
+        if let Some(item) = cmd.get(commands::SHOW_ITEM_DETAIL) {
+            if let Some(workshop_dir) = &self.workshop_dir {
+                let item_dir = workshop_dir.join(item.dir_name());
 
-            let app_id = &data.selected_app.as_ref().unwrap().app_id;
-            let client = workshop_cleaner_core::init(AppId(*app_id));
+                data.detail = Some(ItemDetail::new(
+                    item.clone(),
+                    item_dir.display().to_string(),
+                ));
+                self.load_item_detail(item, item_dir, data.settings.metadata_cache_ttl());
+            }
+
+            return Handled::Yes;
+        }
+
+        if cmd.is(commands::CLOSE_ITEM_DETAIL) {
+            data.detail = None;
+
+            return Handled::Yes;
+        }
 
-            if (client.is_err()) {
-                println!("Failed to init WorkshopCleaner");
+        if let Some((path, preview)) = cmd.get(commands::SET_DETAIL_PREVIEW).cloned() {
+            if let Some(detail) = data.detail.as_mut().filter(|d| d.path == path) {
+                detail.preview_missing = preview.is_none();
+                detail.preview = preview;
+            }
+
+            return Handled::Yes;
+        }
 
+        if let Some((path, files)) = cmd.get(commands::SET_DETAIL_FILES) {
+            if let Some(detail) = data.detail.as_mut().filter(|d| d.path == *path) {
+                detail.largest_files =
+                    Some(files.largest_files.iter().map(FileEntry::from).collect());
+                detail.largest_folders =
+                    Some(files.largest_folders.iter().map(FileEntry::from).collect());
+            }
+
+            return Handled::Yes;
+        }
+
+        if let Some(items) = cmd.get(commands::REQUEST_REMOVE_ITEMS) {
+            // one removal job at a time
+            if !items.is_empty() && !data.is_removal_running() {
+                let message = if items.len() == 1 {
+                    format!("Remove workshop item {} from the disk?", items[0])
+                } else {
+                    format!(
+                        "Remove {} workshop items ({}) from the disk?",
+                        items.len(),
+                        format_size(Some(items.iter().filter_map(|i| i.size).sum()))
+                    )
+                };
+
+                data.dialogs.open(Dialog::confirm(
+                    "Remove items",
+                    message,
+                    commands::CONFIRM_REMOVE_ITEMS.with(items.clone()),
+                ));
+            }
+
+            return Handled::Yes;
+        }
+
+        if let Some(items) = cmd.get(commands::CONFIRM_REMOVE_ITEMS) {
+            if data.is_removal_running() {
                 return Handled::Yes;
             }
+            let strategy = match data.settings.cleaner_removal_strategy() {
+                Ok(strategy) => strategy,
+                Err(e) => {
+                    data.dialogs
+                        .open(Dialog::error("Could not remove items", e));
+                    return Handled::Yes;
+                }
+            };
 
-            self.cleaner = Some(client.unwrap());
+            for item in items.iter() {
+                data.set_removal_state(item.id, RemovalState::Queued);
+            }
+            data.removal_progress = Some((0, items.len()));
 
-            data.items = self
-                .cleaner
-                .as_ref()
-                .unwrap()
-                .get_installed_not_subscribed_items()
-                .into_iter()
-                .map(|i| SteamWorkshopItem(i.0))
-                .collect();
+            let dialog = Dialog::progress("Removing items", "Starting removal...")
+                .with_on_dismiss(commands::CANCEL_REMOVAL);
+            self.removal_dialog = Some(dialog.id);
+            data.dialogs.open(dialog);
+
+            self.session.send(Command::Remove {
+                ids: items.iter().map(|i| i.id).collect(),
+                unsubscribe: data.settings.unsubscribe_on_delete,
+                strategy,
+            });
+
+            return Handled::Yes;
+        }
+
+        if cmd.is(commands::CANCEL_REMOVAL) {
+            self.session.cancel();
+            if let Some(id) = self.removal_dialog {
+                data.dialogs.set_progress(
+                    id,
+                    "Cancelling...",
+                    data.removal_progress.unwrap_or_default(),
+                );
+            }
+
+            return Handled::Yes;
+        }
+
+        if let Some(notification) = cmd.get(commands::NOTIFY).cloned() {
+            data.notifications.push(notification);
+
+            return Handled::Yes;
+        }
+
+        if let Some(id) = cmd.get(commands::DISMISS_NOTIFICATION) {
+            data.notifications.dismiss(*id);
+
+            return Handled::Yes;
+        }
+
+        if let Some(dialog) = cmd.get(commands::OPEN_DIALOG).cloned() {
+            data.dialogs.open(dialog);
+
+            return Handled::Yes;
+        }
+
+        if let Some(dialog) = cmd.get(commands::UPDATE_DIALOG).cloned() {
+            data.dialogs.update(dialog);
+
+            return Handled::Yes;
+        }
+
+        if let Some(id) = cmd.get(commands::CLOSE_DIALOG) {
+            data.dialogs.close(*id);
+
+            return Handled::Yes;
+        }
+
+        if cmd.is(commands::CONFIRM_DIALOG) {
+            if let Some(cmd) = data.dialogs.confirm() {
+                ctx.submit_command(cmd);
+            }
+
+            return Handled::Yes;
+        }
+
+        if cmd.is(commands::DISMISS_DIALOG) {
+            if let Some(cmd) = data.dialogs.dismiss() {
+                ctx.submit_command(cmd);
+            }
 
             return Handled::Yes;
         }
 
         druid::Handled::No
     }
+
+    /// Settings are saved when the window is closed, window size and split position included.
+    fn window_removed(
+        &mut self,
+        _id: druid::WindowId,
+        data: &mut AppState,
+        _env: &druid::Env,
+        _ctx: &mut druid::DelegateCtx,
+    ) {
+        let _ = data.settings.save();
+    }
+
+    /// Enter confirms and Escape dismisses the open dialog, other keys are blocked meanwhile.
+    fn event(
+        &mut self,
+        ctx: &mut druid::DelegateCtx,
+        _window_id: druid::WindowId,
+        event: Event,
+        data: &mut AppState,
+        _env: &druid::Env,
+    ) -> Option<Event> {
+        if data.dialogs.is_empty() {
+            return Some(event);
+        }
+
+        match &event {
+            Event::KeyDown(key) if key.key == KbKey::Enter => {
+                ctx.submit_command(commands::CONFIRM_DIALOG);
+                None
+            }
+            Event::KeyDown(key) if key.key == KbKey::Escape => {
+                ctx.submit_command(commands::DISMISS_DIALOG);
+                None
+            }
+            Event::KeyDown(_) | Event::KeyUp(_) => None,
+            _ => Some(event),
+        }
+    }
+}
+
+fn load_image(path: &Path) -> Option<ImageBuf> {
+    ImageBuf::from_data(&std::fs::read(path).ok()?).ok()
+}
+
+fn is_selected_app(data: &AppState, app_id: u32) -> bool {
+    data.selected_app.as_ref().map(|a| a.app_id) == Some(app_id)
+}
+
+fn removal_notification(summary: &RemovalSummary) -> Notification {
+    match (summary.failed, summary.is_cancelled()) {
+        (0, false) => Notification::info(format!("Removed {} items", summary.removed)),
+        (0, true) => Notification::info(format!(
+            "Removed {} of {} items, cancelled",
+            summary.removed, summary.total
+        )),
+        (failed, _) => Notification::error(format!(
+            "Removed {} of {} items, {} failed",
+            summary.removed, summary.total, failed
+        )),
+    }
 }
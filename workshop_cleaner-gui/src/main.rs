@@ -1,53 +1,97 @@
-#[macro_use]
-extern crate lazy_static;
-
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
 use std::num::NonZeroU64;
 
-use controller::MainController;
-use data::{AppState, SteamApp, SteamWorkshopItem};
+use chrono::DateTime;
+use controller::{MainController, SplitPointController};
+use data::{
+    AppState, FileEntry, ItemDetail, ItemFilter, ItemSort, RemovalState, RemovalStrategy,
+    ScanState, Settings, SteamApp, SteamWorkshopItem, ThemeMode, VisibleItems, VisibleItemsFilter,
+};
 use druid::im::{vector, Vector};
 use druid::lens::{self, LensExt};
+use druid::text::format::ParseFormatter;
 use druid::widget::{
-    Button, Container, Either, Flex, Label, LensWrap, List, Scroll, Split, ViewSwitcher,
+    Button, Checkbox, Container, CrossAxisAlignment, Either, FillStrat, Flex, Image, Label,
+    LensWrap, LineBreaking, List, Painter, Radio, Scroll, Split, TextBox, ViewSwitcher,
 };
 use druid::{
-    AppLauncher, Color, Data, EventCtx, ImageBuf, Lens, Rect, UnitPoint, Widget, WidgetExt,
+    AppLauncher, Data, EventCtx, ImageBuf, Rect, RenderContext, UnitPoint, Widget, WidgetExt,
     WidgetId, WindowDesc,
 };
+use humansize::FileSize;
+use ui::theme::COLOR_ERROR;
+use widget::dialog::dialog_host;
+use widget::keyboard::RowNavigator;
 use widget::list::NavList;
+use widget::notification::notification_host;
+use widget::spinner::spinner_widget;
+use widget::treemap::{BarChart, Treemap};
+use widget::virtual_list::{VirtualList, VirtualScroll};
+use workshop_cleaner_core::cleaner::ItemState;
 
 use delegate::Delegate;
 
+mod cache;
 mod cmd;
 mod controller;
 mod data;
 mod delegate;
-mod steam;
 mod ui;
 mod widget;
 
 fn main() {
-    let main_window = WindowDesc::new(ui_builder)
-        .window_size((1000.0, 512.0))
+    // Steam access of the selected app runs in a helper process started from this binary
+    if let Some(code) = workshop_cleaner_core::helper::run_if_requested() {
+        std::process::exit(code);
+    }
+
+    let settings = Settings::load();
+    let split_point = settings.split_point;
+    let system_dark = ui::theme::system_prefers_dark().unwrap_or(true);
+    let main_window = WindowDesc::new(move || ui_builder(split_point, system_dark))
+        .window_size((settings.window_width, settings.window_height))
         .title("Workshop Cleaner");
 
-    AppLauncher::with_window(main_window)
+    let launcher = AppLauncher::with_window(main_window);
+    let delegate = Delegate::new(launcher.get_external_handle());
+
+    launcher
         .use_simple_logger()
-        .delegate(Delegate::new())
-        .launch(AppState::default())
+        .configure_env(move |env, data: &AppState| {
+            ui::theme::configure_env(env, ui::theme::palette(data.settings.theme, system_dark))
+        })
+        .delegate(delegate)
+        .launch(AppState {
+            settings,
+            ..AppState::default()
+        })
         .expect("App launch failed");
 }
 
-const MAIN_LAYOUT_SPLIT_POINT: f64 = 0.3;
+const DETAIL_LAYOUT_SPLIT_POINT: f64 = 0.6;
+const SUMMARY_COLUMN_WIDTH: f64 = 100.0;
+const SUMMARY_ROW_HEIGHT: f64 = 30.0;
+const ITEM_ROW_HEIGHT: f64 = 60.0;
+const TREEMAP_HEIGHT: f64 = 160.0;
+const ITEM_DETAILS_TEXT_SIZE: f64 = 12.0;
+const PREVIEW_HEIGHT: f64 = 180.0;
+const SETTINGS_LABEL_WIDTH: f64 = 200.0;
 
 type NavListState = (Option<Vector<SteamApp>>, Option<SteamApp>);
 
-fn ui_builder() -> impl Widget<AppState> {
-    Split::columns(nav_list_widget(), items_widget())
-        .split_point(MAIN_LAYOUT_SPLIT_POINT)
+fn ui_builder(split_point: f64, system_dark: bool) -> impl Widget<AppState> {
+    let layout = Split::columns(nav_list_widget(), items_widget())
+        .split_point(split_point)
         .solid_bar(true)
         .draggable(true)
+        .controller(SplitPointController);
+
+    // the scope applies theme changes made in the settings while the app runs
+    dialog_host(notification_host(layout))
+        .background(ui::theme::COLOR_BACKGROUND)
+        .env_scope(move |env, data: &AppState| {
+            ui::theme::configure_env(env, ui::theme::palette(data.settings.theme, system_dark))
+        })
         .controller(MainController::new())
     // .debug_paint_layout()
 }
@@ -69,76 +113,561 @@ fn nav_list_widget() -> impl Widget<AppState> {
         |data: &AppState| (data.apps.clone(), data.selected_app.clone()),
         |_, _| (),
     ));
+    let buttons = Flex::row()
+        .with_child(
+            Button::new("Settings").on_click(|_, data: &mut AppState, _| data.settings_open = true),
+        )
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(Button::new("Log").on_click(|_, data: &mut AppState, _| {
+            data.notifications.log_open = !data.notifications.log_open
+        }))
+        .padding(ui::theme::NAV_LIST_ITEM_PADDING);
 
-    Container::new(view_switcher)
+    Flex::column()
+        .with_flex_child(Container::new(view_switcher), 1.0)
+        .with_child(buttons)
 }
 
 fn app_list_widget() -> impl Widget<NavListState> {
     let list = widget::list::NavList::new(|ctx, data: &SteamApp| {
         ctx.submit_command(cmd::SELECT_STEAM_APP.with(data.clone()))
-    });
+    })
+    .with_auto_focus(true);
 
-    Scroll::new(list).vertical()
+    VirtualScroll::new(list)
 }
 
 fn items_widget() -> impl Widget<AppState> {
     let view_switcher = ViewSwitcher::new(
         |data: &AppState, _| data.selected_app.clone(),
         |selected_app, _, _| match selected_app {
-            Some(_) => Box::new(Either::new(
-                |data: &AppState, _| !data.selected_app_confirmed,
-                items_confirm_widget(),
-                Either::new(
-                    |data: &AppState, _| !data.items.is_empty(),
-                    List::new(item_widget).lens(AppState::items),
-                    Label::new("Nothing found, your workshop is clean.")
-                        .padding(20.)
-                        .align_horizontal(UnitPoint::TOP)
-                        .expand(),
-                ),
+            Some(_) => Box::new(ViewSwitcher::new(
+                |data: &AppState, _| data.scan.clone(),
+                |scan, _, _| match scan {
+                    ScanState::Scanning(text) => Box::new(
+                        Flex::column()
+                            .with_child(spinner_widget())
+                            .with_child(Label::new(text.clone()))
+                            .padding(20.)
+                            .align_horizontal(UnitPoint::TOP)
+                            .expand(),
+                    ),
+                    ScanState::Failed(e) => Box::new(
+                        Flex::column()
+                            .with_child(Label::new(e.clone()).with_text_color(COLOR_ERROR))
+                            .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+                            .with_child(Button::new("Retry").on_click(
+                                |ctx, data: &mut AppState, _| {
+                                    if let Some(app) = data.selected_app.clone() {
+                                        ctx.submit_command(cmd::SELECT_STEAM_APP.with(app))
+                                    }
+                                },
+                            ))
+                            .padding(20.)
+                            .align_horizontal(UnitPoint::TOP)
+                            .expand(),
+                    ),
+                    ScanState::Done => Box::new(scanned_items_widget()),
+                },
             )),
-            None => Box::new(
-                Label::new("Select app to continue...")
-                    .padding(20.)
-                    .align_horizontal(UnitPoint::TOP)
-                    .expand_width(),
-            ),
+            None => Box::new(summary_widget()),
         },
     );
 
-    Container::new(view_switcher)
+    Either::new(
+        |data: &AppState, _| data.settings_open,
+        settings_widget(),
+        Container::new(view_switcher),
+    )
+}
+
+/// Settings screen shown instead of the items, changes are saved when it's closed.
+fn settings_widget() -> impl Widget<AppState> {
+    let fields = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(settings_row(
+            "Theme",
+            Flex::row()
+                .with_child(Radio::new("System", ThemeMode::System))
+                .with_child(Radio::new("Light", ThemeMode::Light))
+                .with_child(Radio::new("Dark", ThemeMode::Dark))
+                .lens(Settings::theme),
+        ))
+        .with_child(settings_row(
+            "Steam folder",
+            TextBox::new()
+                .with_placeholder("Detected automatically")
+                .expand_width()
+                .lens(Settings::steam_root),
+        ))
+        .with_child(settings_row(
+            "Unsubscribe on delete",
+            Checkbox::new("").lens(Settings::unsubscribe_on_delete),
+        ))
+        .with_child(settings_row(
+            "Removed items",
+            Flex::row()
+                .with_child(Radio::new("Delete", RemovalStrategy::Delete))
+                .with_child(Radio::new("Move to folder", RemovalStrategy::MoveToFolder))
+                .lens(Settings::removal_strategy),
+        ))
+        .with_child(settings_row(
+            "Folder for removed items",
+            TextBox::new()
+                .with_placeholder("Used with \"Move to folder\"")
+                .expand_width()
+                .lens(Settings::removal_folder),
+        ))
+        .with_child(settings_row(
+            "Keep previews (days)",
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .fix_width(SUMMARY_COLUMN_WIDTH)
+                .lens(Settings::metadata_cache_ttl_days),
+        ))
+        .lens(AppState::settings);
+    let done = Button::new("Done").on_click(|ctx, data: &mut AppState, _| {
+        data.settings_open = false;
+        ctx.submit_command(cmd::SAVE_SETTINGS);
+    });
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Settings").with_text_size(18.0))
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(fields)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(done)
+        .padding(20.)
+        .align_vertical(UnitPoint::TOP)
 }
 
-fn items_confirm_widget() -> impl Widget<AppState> {
-    let widget = Flex::column();
+fn settings_row(label: &str, field: impl Widget<Settings> + 'static) -> impl Widget<Settings> {
+    Flex::row()
+        .with_child(Label::new(label).fix_width(SETTINGS_LABEL_WIDTH))
+        .with_flex_child(field, 1.0)
+        .padding((0.0, ui::theme::NAV_LIST_ITEM_PADDING / 2.0))
+}
+
+fn scanned_items_widget() -> impl Widget<AppState> {
+    let items = || {
+        Either::new(
+            |data: &AppState, _| !data.items.is_empty(),
+            items_list_widget(),
+            Label::new("Nothing found, your workshop is clean.")
+                .padding(20.)
+                .align_horizontal(UnitPoint::TOP)
+                .expand(),
+        )
+    };
+
+    Either::new(
+        |data: &AppState, _| data.detail.is_some(),
+        Split::columns(items(), item_detail_widget())
+            .split_point(DETAIL_LAYOUT_SPLIT_POINT)
+            .solid_bar(true)
+            .draggable(true),
+        items(),
+    )
+}
 
-    let label = Label::new(
-        "Please confirm app selection, you will need to restart the application to change it.",
+/// Pane with details of the clicked item, shows where its space on the disk goes.
+fn item_detail_widget() -> impl Widget<AppState> {
+    let header = Flex::row()
+        .with_flex_child(
+            Label::new(|data: &AppState, _env: &_| {
+                data.detail_item()
+                    .map(|i| i.to_string())
+                    .unwrap_or_default()
+            })
+            .with_text_size(18.0)
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .expand_width(),
+            1.0,
+        )
+        .with_child(
+            Button::new("Close").on_click(|ctx, _, _| ctx.submit_command(cmd::CLOSE_ITEM_DETAIL)),
+        );
+    let preview = ViewSwitcher::new(
+        |data: &AppState, _| {
+            data.detail
+                .as_ref()
+                .map(|d| (d.preview.clone(), d.preview_missing))
+        },
+        |preview, _, _| match preview {
+            Some((Some(image), _)) => Box::new(preview_widget(image)),
+            Some((None, true)) => Box::new(
+                Label::new("No preview image")
+                    .with_text_color(ui::theme::COLOR_TEXT_MUTED)
+                    .center()
+                    .fix_height(PREVIEW_HEIGHT),
+            ),
+            _ => Box::new(spinner_widget().center().fix_height(PREVIEW_HEIGHT)),
+        },
+    );
+    let info = Label::new(
+        |data: &AppState, _env: &_| match (data.detail_item(), &data.detail) {
+            (Some(item), Some(detail)) => format!(
+                "Author: {}\nSize: {}\nState: {}\nPath: {}",
+                item.author.as_deref().unwrap_or("Unknown"),
+                format_size(item.size),
+                format_item_state(item.state),
+                detail.path
+            ),
+            _ => String::new(),
+        },
     )
-    .padding(20.)
-    .align_horizontal(UnitPoint::TOP)
-    .expand_width();
-    let button =
-        Button::new("Confirm").on_click(|ctx, _, _| ctx.submit_command(cmd::CONFIRM_STEAM_APP));
+    .with_line_break_mode(LineBreaking::WordWrap);
+    let description = Label::new(|data: &AppState, _env: &_| {
+        data.detail_item()
+            .and_then(|i| i.description)
+            .unwrap_or_else(|| "No description".to_string())
+    })
+    .with_text_size(ITEM_DETAILS_TEXT_SIZE)
+    .with_line_break_mode(LineBreaking::WordWrap);
+
+    let content = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(header)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(preview)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(info)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(description)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(file_list_widget("Largest folders", |d| {
+            d.largest_folders.clone()
+        }))
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(file_list_widget("Largest files", |d| {
+            d.largest_files.clone()
+        }))
+        .padding(ui::theme::NAV_LIST_ITEM_PADDING);
+
+    Scroll::new(content).vertical()
+}
 
-    widget.with_child(label).with_child(button)
+fn preview_widget(image: &ImageBuf) -> impl Widget<AppState> {
+    Image::new(image.clone())
+        .fill_mode(FillStrat::Contain)
+        .expand_width()
+        .fix_height(PREVIEW_HEIGHT)
 }
 
-fn item_widget<T>() -> impl Widget<T>
-where
-    T: Data + Display,
-{
+/// Largest files or folders of the opened item, spinner is shown until they are read.
+fn file_list_widget(
+    title: &str,
+    files: impl Fn(&ItemDetail) -> Option<Vector<FileEntry>> + 'static,
+) -> impl Widget<AppState> {
+    let list = ViewSwitcher::new(
+        move |data: &AppState, _| data.detail.as_ref().and_then(&files),
+        |files, _, _| match files {
+            Some(files) if files.is_empty() => Box::new(Label::new("Nothing found")),
+            Some(files) => {
+                let mut list = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+                for file in files.iter() {
+                    list.add_child(
+                        Label::new(format!("{}  {}", format_size(Some(file.size)), file.path))
+                            .with_text_size(ITEM_DETAILS_TEXT_SIZE),
+                    );
+                }
+
+                Box::new(list)
+            }
+            None => Box::new(spinner_widget()),
+        },
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(title))
+        .with_child(list)
+}
+
+/// Table of all apps, showing where most space can be reclaimed, click on a row selects the app.
+fn summary_widget() -> impl Widget<AppState> {
+    let header = summary_row(
+        Label::new("App"),
+        Label::new("Installed"),
+        Label::new("Unsubscribed by all"),
+        Label::new("Reclaimable"),
+    );
+    let apps = || {
+        lens::Map::new(
+            |data: &AppState| data.apps.clone().unwrap_or_default(),
+            |_, _| (),
+        )
+    };
+    let rows = List::new(summary_app_widget).lens(apps());
+    let chart = BarChart::new().lens(apps());
+
+    Flex::column()
+        .with_child(
+            Label::new("Select app to continue...")
+                .padding(20.)
+                .align_horizontal(UnitPoint::TOP)
+                .expand_width(),
+        )
+        .with_flex_child(
+            Scroll::new(
+                Flex::column()
+                    .with_child(chart)
+                    .with_spacer(20.)
+                    .with_child(header)
+                    .with_child(rows)
+                    .padding((20., 0.)),
+            )
+            .vertical(),
+            1.0,
+        )
+}
+
+fn summary_row<T: Data>(
+    app: impl Widget<T> + 'static,
+    installed: impl Widget<T> + 'static,
+    orphaned: impl Widget<T> + 'static,
+    reclaimable: impl Widget<T> + 'static,
+) -> impl Widget<T> {
     Flex::row()
+        .with_flex_child(app.expand_width(), 1.0)
+        .with_child(installed.align_right().fix_width(SUMMARY_COLUMN_WIDTH))
+        .with_child(orphaned.align_right().fix_width(SUMMARY_COLUMN_WIDTH * 1.5))
+        .with_child(reclaimable.align_right().fix_width(SUMMARY_COLUMN_WIDTH))
+        .fix_height(SUMMARY_ROW_HEIGHT)
+}
+
+fn summary_app_widget() -> impl Widget<SteamApp> {
+    summary_row(
+        Label::new(|app: &SteamApp, _env: &_| format!("{}", app)),
+        Label::new(|app: &SteamApp, _env: &_| app.installed_items.to_string()),
+        Label::new(|app: &SteamApp, _env: &_| app.orphaned_items.to_string()),
+        Label::new(|app: &SteamApp, _env: &_| format_size(Some(app.reclaimable_bytes))),
+    )
+    .on_click(|ctx, app: &mut SteamApp, _| {
+        ctx.submit_command(cmd::SELECT_STEAM_APP.with(app.clone()))
+    })
+}
+
+/// Items list with selection toolbar on the top and removal of selected items on the bottom.
+fn items_list_widget() -> impl Widget<AppState> {
+    let toolbar = Flex::row()
+        .with_child(
+            Button::new("Select all").on_click(|_, data: &mut AppState, _| data.select_all_items()),
+        )
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(
+            Button::new("Select none").on_click(|_, data: &mut AppState, _| data.select_no_items()),
+        )
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
         .with_child(
-            Label::new(|item: &T, _env: &_| format!("{}", item)).align_vertical(UnitPoint::LEFT),
+            Button::new("Invert")
+                .on_click(|_, data: &mut AppState, _| data.invert_item_selection()),
         )
+        .padding(ui::theme::NAV_LIST_ITEM_PADDING);
+    // Enter or Space selects the focused item, Delete removes the selection
+    let rows = RowNavigator::new(
+        VirtualList::new(ITEM_ROW_HEIGHT, item_widget),
+        ITEM_ROW_HEIGHT,
+        |_, item: &mut SteamWorkshopItem| item.selected = !item.selected,
+    )
+    .with_on_delete(|ctx, items, focused| {
+        let mut selected: Vector<SteamWorkshopItem> =
+            items.iter().filter(|i| i.selected).cloned().collect();
+        if selected.is_empty() {
+            selected.push_back(items[focused].clone());
+        }
+        ctx.submit_command(cmd::REQUEST_REMOVE_ITEMS.with(selected))
+    });
+    let list = VirtualScroll::new(rows).lens(VisibleItems);
+    let treemap = Treemap::new()
+        .lens(VisibleItems)
+        .fix_height(TREEMAP_HEIGHT)
+        .padding(ui::theme::NAV_LIST_ITEM_PADDING);
+
+    Flex::column()
+        .with_child(item_filter_widget().lens(VisibleItemsFilter))
+        .with_child(treemap)
+        .with_child(toolbar)
+        .with_flex_child(list, 1.0)
+        .with_child(items_footer_widget())
+}
+
+/// Search, state filters, minimum size and sorting of the items list.
+fn item_filter_widget() -> impl Widget<ItemFilter> {
+    let search = Flex::row()
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("Search title or ID")
+                .expand_width()
+                .lens(ItemFilter::search),
+            1.0,
+        )
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(Label::new("Min size (MB)"))
+        .with_child(
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .fix_width(SUMMARY_COLUMN_WIDTH)
+                .lens(ItemFilter::min_size_mb),
+        );
+    let states = Flex::row()
+        .with_child(Checkbox::new("Not subscribed").lens(ItemFilter::show_unsubscribed))
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(Checkbox::new("Subscribed").lens(ItemFilter::show_pinned))
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(Checkbox::new("Downloading").lens(ItemFilter::show_in_use))
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(Checkbox::new("Unknown folders").lens(ItemFilter::show_unknown_folders));
+    let sort = Flex::row()
+        .with_child(Label::new("Sort by"))
+        .with_child(Radio::new("Size", ItemSort::Size))
+        .with_child(Radio::new("Title", ItemSort::Title))
+        .with_child(Radio::new("ID", ItemSort::Id))
+        .with_child(Radio::new("Last updated", ItemSort::Updated))
+        .lens(ItemFilter::sort);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(search)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(states)
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(sort)
+        .padding(ui::theme::NAV_LIST_ITEM_PADDING)
+}
+
+/// Selection summary, removal progress is shown in a dialog.
+fn items_footer_widget() -> impl Widget<AppState> {
+    Flex::row()
+        .with_child(Label::new(|data: &AppState, _env: &_| {
+            format!(
+                "Selected {} items, {}",
+                data.selected_items().len(),
+                format_size(Some(data.selected_size()))
+            )
+        }))
         .with_flex_spacer(1.0)
         .with_child(
-            Button::new("Delete")
-                .fix_size(80.0, 30.0)
-                .align_vertical(UnitPoint::CENTER),
+            Button::new("Remove selected").on_click(|ctx, data: &mut AppState, _| {
+                ctx.submit_command(cmd::REQUEST_REMOVE_ITEMS.with(data.selected_items()))
+            }),
         )
         .padding(ui::theme::NAV_LIST_ITEM_PADDING)
-        .background(Color::rgb(0.5, 0.0, 0.5))
         .fix_height(ui::theme::NAV_LIST_ITEM_HEIGHT)
 }
+
+fn item_widget() -> impl Widget<SteamWorkshopItem> {
+    let removal = ViewSwitcher::new(
+        |item: &SteamWorkshopItem, _| item.removal.clone(),
+        |removal, _, _| match removal {
+            RemovalState::Installed => Box::new(Either::new(
+                |item: &SteamWorkshopItem, _| item.is_removable(),
+                Button::new("Delete")
+                    .on_click(|ctx, item: &mut SteamWorkshopItem, _| {
+                        ctx.submit_command(cmd::REQUEST_REMOVE_ITEMS.with(vector![item.clone()]))
+                    })
+                    .fix_size(80.0, 30.0),
+                Label::new(|item: &SteamWorkshopItem, _env: &_| {
+                    format_item_state(item.state).to_string()
+                }),
+            )),
+            RemovalState::Queued => Box::new(Label::new("Queued")),
+            RemovalState::Removing => Box::new(
+                Flex::row()
+                    .with_child(spinner_widget())
+                    .with_child(Label::new("Removing...")),
+            ),
+            RemovalState::Removed => Box::new(Label::new("Removed")),
+            RemovalState::Failed(reason) => Box::new(
+                Flex::row()
+                    .with_child(Label::new(format!("Failed: {}", reason)))
+                    .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+                    .with_child(
+                        Button::new("Retry")
+                            .on_click(|ctx, item: &mut SteamWorkshopItem, _| {
+                                ctx.submit_command(
+                                    cmd::REQUEST_REMOVE_ITEMS.with(vector![item.clone()]),
+                                )
+                            })
+                            .fix_size(80.0, 30.0),
+                    ),
+            ),
+        },
+    );
+
+    Flex::row()
+        .with_child(Checkbox::new("").lens(SteamWorkshopItem::selected))
+        .with_flex_child(
+            Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(Label::new(|item: &SteamWorkshopItem, _env: &_| {
+                    format!("{}", item)
+                }))
+                .with_child(
+                    Label::new(|item: &SteamWorkshopItem, _env: &_| format_item_details(item))
+                        .with_text_size(ITEM_DETAILS_TEXT_SIZE)
+                        .with_text_color(ui::theme::COLOR_TEXT_MUTED),
+                )
+                .align_vertical(UnitPoint::LEFT),
+            1.0,
+        )
+        .with_child(Label::new(|item: &SteamWorkshopItem, _env: &_| {
+            format_size(item.size)
+        }))
+        .with_spacer(ui::theme::NAV_LIST_ITEM_PADDING)
+        .with_child(removal.align_vertical(UnitPoint::CENTER))
+        .padding(ui::theme::NAV_LIST_ITEM_PADDING)
+        .background(Painter::new(|ctx, _: &SteamWorkshopItem, env| {
+            let rect = ctx.size().to_rect();
+            if ctx.is_hot() {
+                ctx.fill(rect, &env.get(ui::theme::COLOR_SURFACE_HOT));
+            } else {
+                ctx.fill(rect, &env.get(ui::theme::COLOR_SURFACE));
+            }
+        }))
+        .fix_height(ITEM_ROW_HEIGHT)
+        .on_click(|ctx, item: &mut SteamWorkshopItem, _| {
+            ctx.submit_command(cmd::SHOW_ITEM_DETAIL.with(item.clone()))
+        })
+}
+
+/// Secondary line of the item row, parts that are not loaded yet are left out.
+fn format_item_details(item: &SteamWorkshopItem) -> String {
+    let mut details = vec![];
+
+    if let Some(author) = &item.author {
+        details.push(format!("by {}", author));
+    }
+    if let Some(time_updated) = item.time_updated {
+        details.push(format!("updated {}", format_timestamp(time_updated)));
+    }
+    if item.state != ItemState::Unsubscribed {
+        details.push(format_item_state(item.state).to_string());
+    }
+
+    details.join(" · ")
+}
+
+fn format_item_state(state: ItemState) -> &'static str {
+    match state {
+        ItemState::Unsubscribed => "Not subscribed",
+        ItemState::Pinned => "Subscribed",
+        ItemState::InUse => "Downloading",
+        ItemState::UnknownFolder => "Unknown folder",
+    }
+}
+
+fn format_timestamp(timestamp: u64) -> String {
+    match DateTime::from_timestamp(timestamp as i64, 0) {
+        Some(t) => t.format("%Y-%m-%d").to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+fn format_size(size: Option<u64>) -> String {
+    match size {
+        Some(size) => size.file_size(humansize::file_size_opts::BINARY).unwrap(),
+        None => "Unknown size".to_string(),
+    }
+}
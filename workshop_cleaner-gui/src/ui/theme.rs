@@ -1,16 +1,182 @@
-use ::druid::Color;
+use std::process::Command;
+
+use ::druid::{Color, Env, Key};
 pub use druid::theme as druid;
 
+use crate::data::ThemeMode;
+
 pub fn grid(m: f64) -> f64 {
     GRID * m
 }
 
 pub const GRID: f64 = 8.0;
 
-pub const COLOR_TRANSPARENT: Color = Color::rgba8(0, 0, 0, 0);
+pub const COLOR_BACKGROUND: Key<Color> = Key::new("workshop_cleaner.theme.background");
+/// Background of rows, dialogs and banners.
+pub const COLOR_SURFACE: Key<Color> = Key::new("workshop_cleaner.theme.surface");
+pub const COLOR_SURFACE_HOT: Key<Color> = Key::new("workshop_cleaner.theme.surface_hot");
+pub const COLOR_TEXT: Key<Color> = Key::new("workshop_cleaner.theme.text");
+pub const COLOR_TEXT_MUTED: Key<Color> = Key::new("workshop_cleaner.theme.text_muted");
+/// Focus marks, selection outlines and the active spinner dot.
+pub const COLOR_ACCENT: Key<Color> = Key::new("workshop_cleaner.theme.accent");
+/// Dims content below modal overlays.
+pub const COLOR_OVERLAY: Key<Color> = Key::new("workshop_cleaner.theme.overlay");
+
+pub const COLOR_STATE_UNSUBSCRIBED: Color = Color::rgb8(0xef, 0x8a, 0x3c);
+pub const COLOR_STATE_PINNED: Color = Color::rgb8(0x42, 0x8b, 0xca);
+pub const COLOR_STATE_IN_USE: Color = Color::rgb8(0x5c, 0xa8, 0x5f);
+pub const COLOR_STATE_UNKNOWN: Color = Color::grey8(0x82);
 
-pub const COLOR_GREY_400: Color = Color::grey8(0x82);
-pub const COLOR_GREY_500: Color = Color::grey8(0x4f);
+pub const COLOR_ERROR: Color = Color::rgb8(0xef, 0x53, 0x50);
+pub const COLOR_WARNING: Color = Color::rgb8(0xff, 0xb7, 0x4d);
+pub const COLOR_INFO: Color = Color::rgb8(0x42, 0x8b, 0xca);
 
 pub const NAV_LIST_ITEM_HEIGHT: f64 = 50.0;
 pub const NAV_LIST_ITEM_PADDING: f64 = 10.0;
+
+/// Keeps `reg` from flashing a console window.
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Colors of one theme, applied to the `Env` by `configure_env`.
+pub struct Palette {
+    pub background: Color,
+    pub surface: Color,
+    pub surface_hot: Color,
+    pub text: Color,
+    pub text_muted: Color,
+    pub accent: Color,
+    pub overlay: Color,
+    pub button_light: Color,
+    pub button_dark: Color,
+    pub border: Color,
+    pub input_background: Color,
+}
+
+pub const DARK: Palette = Palette {
+    background: Color::rgb8(0x29, 0x29, 0x29),
+    surface: Color::rgb8(0x33, 0x33, 0x36),
+    surface_hot: Color::rgb8(0x45, 0x45, 0x4a),
+    text: Color::rgb8(0xf0, 0xf0, 0xea),
+    text_muted: Color::grey8(0x9a),
+    accent: Color::rgb8(0x5c, 0xc4, 0xff),
+    overlay: Color::rgba8(0, 0, 0, 0x80),
+    button_light: Color::rgb8(0x4a, 0x4a, 0x4f),
+    button_dark: Color::rgb8(0x3a, 0x3a, 0x3e),
+    border: Color::rgb8(0x5a, 0x5a, 0x5a),
+    input_background: Color::rgb8(0x1f, 0x1f, 0x1f),
+};
+
+pub const LIGHT: Palette = Palette {
+    background: Color::rgb8(0xf5, 0xf5, 0xf5),
+    surface: Color::rgb8(0xff, 0xff, 0xff),
+    surface_hot: Color::rgb8(0xe3, 0xe8, 0xef),
+    text: Color::rgb8(0x21, 0x21, 0x21),
+    text_muted: Color::grey8(0x6b),
+    accent: Color::rgb8(0x00, 0x78, 0xd4),
+    overlay: Color::rgba8(0, 0, 0, 0x40),
+    button_light: Color::rgb8(0xfa, 0xfa, 0xfa),
+    button_dark: Color::rgb8(0xe0, 0xe0, 0xe0),
+    border: Color::rgb8(0xb8, 0xb8, 0xb8),
+    input_background: Color::rgb8(0xff, 0xff, 0xff),
+};
+
+/// Palette of the chosen mode, `system_dark` is used when it follows the system.
+pub fn palette(mode: ThemeMode, system_dark: bool) -> &'static Palette {
+    match mode {
+        ThemeMode::Light => &LIGHT,
+        ThemeMode::Dark => &DARK,
+        ThemeMode::System if system_dark => &DARK,
+        ThemeMode::System => &LIGHT,
+    }
+}
+
+/// Set app colors and the matching druid theme colors.
+pub fn configure_env(env: &mut Env, palette: &Palette) {
+    env.set(COLOR_BACKGROUND, palette.background.clone());
+    env.set(COLOR_SURFACE, palette.surface.clone());
+    env.set(COLOR_SURFACE_HOT, palette.surface_hot.clone());
+    env.set(COLOR_TEXT, palette.text.clone());
+    env.set(COLOR_TEXT_MUTED, palette.text_muted.clone());
+    env.set(COLOR_ACCENT, palette.accent.clone());
+    env.set(COLOR_OVERLAY, palette.overlay.clone());
+
+    env.set(druid::WINDOW_BACKGROUND_COLOR, palette.background.clone());
+    env.set(druid::LABEL_COLOR, palette.text.clone());
+    env.set(druid::PLACEHOLDER_COLOR, palette.text_muted.clone());
+    env.set(druid::PRIMARY_LIGHT, palette.accent.clone());
+    env.set(druid::PRIMARY_DARK, palette.accent.clone());
+    env.set(druid::BACKGROUND_LIGHT, palette.input_background.clone());
+    env.set(druid::BACKGROUND_DARK, palette.input_background.clone());
+    env.set(druid::FOREGROUND_LIGHT, palette.text.clone());
+    env.set(druid::FOREGROUND_DARK, palette.text_muted.clone());
+    env.set(druid::BUTTON_LIGHT, palette.button_light.clone());
+    env.set(druid::BUTTON_DARK, palette.button_dark.clone());
+    env.set(druid::BORDER_LIGHT, palette.accent.clone());
+    env.set(druid::BORDER_DARK, palette.border.clone());
+    env.set(druid::SELECTION_COLOR, palette.accent.clone());
+    env.set(
+        druid::SELECTION_TEXT_COLOR,
+        palette.input_background.clone(),
+    );
+    env.set(druid::CURSOR_COLOR, palette.text.clone());
+    env.set(druid::SCROLLBAR_COLOR, palette.text_muted.clone());
+    env.set(druid::SCROLLBAR_BORDER_COLOR, palette.border.clone());
+}
+
+/// Whether the system uses a dark theme, `None` if it could not be detected.
+pub fn system_prefers_dark() -> Option<bool> {
+    if let Ok(gtk_theme) = std::env::var("GTK_THEME") {
+        return Some(gtk_theme.to_lowercase().contains("dark"));
+    }
+
+    if cfg!(target_os = "windows") {
+        let value = command_output(
+            "reg",
+            &[
+                "query",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+                "/v",
+                "AppsUseLightTheme",
+            ],
+        )?;
+        Some(value.trim_end().ends_with("0x0"))
+    } else if cfg!(target_os = "macos") {
+        // the key only exists while dark mode is on
+        let style = command_output("defaults", &["read", "-g", "AppleInterfaceStyle"]);
+        Some(style.map_or(false, |s| s.trim() == "Dark"))
+    } else {
+        let scheme = command_output(
+            "gsettings",
+            &["get", "org.gnome.desktop.interface", "color-scheme"],
+        );
+        match scheme.as_deref().map(str::trim) {
+            Some("'prefer-dark'") => Some(true),
+            Some("'prefer-light'") => Some(false),
+            _ => command_output(
+                "gsettings",
+                &["get", "org.gnome.desktop.interface", "gtk-theme"],
+            )
+            .map(|theme| theme.to_lowercase().contains("dark")),
+        }
+    }
+}
+
+/// Stdout of a successful command.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command.output().ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,104 @@
+use druid::widget::{Button, Flex, Label, ProgressBar, ViewSwitcher};
+use druid::{Widget, WidgetExt};
+
+use crate::{
+    cmd,
+    data::{AppState, DialogKind, DialogQueue},
+    ui::theme,
+    widget::{spinner::spinner_widget, stacked_container::StackedContainer},
+};
+
+const DIALOG_WIDTH: f64 = 400.0;
+
+/// Show dialogs of the queue on top of the content, the content gets no events meanwhile.
+pub fn dialog_host(content: impl Widget<AppState> + 'static) -> impl Widget<AppState> {
+    StackedContainer::new(content, dialog_widget().lens(AppState::dialogs))
+        .with_show_overlay_callback(Box::new(|data: &AppState| !data.dialogs.is_empty()))
+}
+
+/// Current dialog of the queue, centered over a dimmed background.
+fn dialog_widget() -> impl Widget<DialogQueue> {
+    let dialog = ViewSwitcher::new(
+        |queue: &DialogQueue, _| {
+            queue
+                .current()
+                .map(|d| (d.id, d.kind, d.on_dismiss.is_some()))
+        },
+        |current, _, _| match current {
+            Some((_, kind, cancellable)) => Box::new(dialog_content(*kind, *cancellable)),
+            None => Box::new(Flex::column()),
+        },
+    );
+
+    dialog
+        .padding(20.)
+        .fix_width(DIALOG_WIDTH)
+        .background(theme::COLOR_SURFACE)
+        .center()
+        .expand()
+        .background(theme::COLOR_OVERLAY)
+}
+
+fn dialog_content(kind: DialogKind, cancellable: bool) -> impl Widget<DialogQueue> {
+    let title = Label::new(|queue: &DialogQueue, _env: &_| {
+        queue.current().map(|d| d.title.clone()).unwrap_or_default()
+    })
+    .with_text_size(18.0);
+    let message = Label::new(|queue: &DialogQueue, _env: &_| {
+        queue
+            .current()
+            .map(|d| d.message.clone())
+            .unwrap_or_default()
+    })
+    .with_line_break_mode(druid::widget::LineBreaking::WordWrap);
+
+    let mut content = Flex::column()
+        .cross_axis_alignment(druid::widget::CrossAxisAlignment::Start)
+        .with_child(title)
+        .with_spacer(theme::grid(1.0))
+        .with_child(message)
+        .with_spacer(theme::grid(2.0));
+
+    let buttons = match kind {
+        DialogKind::Confirm => Flex::row()
+            .with_flex_spacer(1.0)
+            .with_child(dismiss_button("Cancel"))
+            .with_spacer(theme::grid(1.0))
+            .with_child(
+                Button::new("OK").on_click(|ctx, _, _| ctx.submit_command(cmd::CONFIRM_DIALOG)),
+            ),
+        DialogKind::Error => Flex::row()
+            .with_flex_spacer(1.0)
+            .with_child(dismiss_button("Close")),
+        DialogKind::Progress => {
+            content = content
+                .with_child(
+                    ProgressBar::new()
+                        .lens(druid::lens::Map::new(
+                            |queue: &DialogQueue| match queue.current().and_then(|d| d.progress) {
+                                Some((done, total)) if total > 0 => done as f64 / total as f64,
+                                _ => 0.0,
+                            },
+                            |_, _| (),
+                        ))
+                        .expand_width(),
+                )
+                .with_spacer(theme::grid(2.0));
+
+            let buttons = Flex::row()
+                .with_child(spinner_widget())
+                .with_flex_spacer(1.0);
+            if cancellable {
+                buttons.with_child(dismiss_button("Cancel"))
+            } else {
+                buttons
+            }
+        }
+    };
+
+    content.with_child(buttons.expand_width())
+}
+
+fn dismiss_button(text: &str) -> impl Widget<DialogQueue> {
+    Button::new(text).on_click(|ctx, _, _| ctx.submit_command(cmd::DISMISS_DIALOG))
+}
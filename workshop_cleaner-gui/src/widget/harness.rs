@@ -0,0 +1,52 @@
+//! Events and constraints for widget tests.
+//!
+//! Druid 0.7 keeps its own widget harness private and has no headless backend, so
+//! `Widget::event` and `Widget::layout` can't run without a window. Widgets decide what
+//! an event does and how large they are in methods without a context, tests feed them
+//! the same `Event` and `BoxConstraints` values a window would send.
+
+use druid::{
+    BoxConstraints, Event, KbKey, KeyEvent, Modifiers, MouseButton, MouseButtons, MouseEvent,
+    Point, Selector, Size, Vec2,
+};
+
+fn mouse(pos: Point, button: MouseButton) -> MouseEvent {
+    let mut buttons = MouseButtons::new();
+    if button != MouseButton::None {
+        buttons.insert(button);
+    }
+
+    MouseEvent {
+        pos,
+        window_pos: pos,
+        buttons,
+        mods: Modifiers::empty(),
+        count: 1,
+        focus: false,
+        button,
+        wheel_delta: Vec2::ZERO,
+    }
+}
+
+/// Left button pressed at `pos`.
+pub fn click(pos: Point) -> Event {
+    Event::MouseDown(mouse(pos, MouseButton::Left))
+}
+
+pub fn mouse_move(pos: Point) -> Event {
+    Event::MouseMove(mouse(pos, MouseButton::None))
+}
+
+pub fn key_down(key: KbKey) -> Event {
+    Event::KeyDown(KeyEvent::for_test(Modifiers::empty(), key))
+}
+
+/// Command sent to the widget, like `ctx.submit_command(selector)`.
+pub fn command(selector: Selector) -> Event {
+    Event::Command(selector.into())
+}
+
+/// Constraints of a window of the size, the widget may be smaller.
+pub fn window(width: f64, height: f64) -> BoxConstraints {
+    BoxConstraints::new(Size::ZERO, Size::new(width, height))
+}
@@ -0,0 +1,321 @@
+//! Keyboard model of the lists: arrows, Home/End, Page Up/Down, type-ahead and Enter.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use druid::{
+    im::Vector, BoxConstraints, Data, Env, Event, EventCtx, KbKey, KeyEvent, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, Rect, RenderContext, Selector, Size, UpdateCtx, Widget, WidgetId,
+};
+
+use crate::ui::theme;
+use crate::widget::virtual_list::Virtualized;
+
+/// Asks the `VirtualScroll` around a list to show the rect, in coordinates of the list.
+pub const SCROLL_TO_VIEW: Selector<Rect> = Selector::new("keyboard.scroll-to-view");
+
+/// Typed letters are joined into one search while keys follow each other within this time.
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+const PAGE_ROWS: usize = 10;
+const FOCUS_RING_WIDTH: f64 = 2.0;
+
+/// What a key pressed in a focused list asks for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListKey {
+    /// The cursor moved to the row.
+    Move(usize),
+    /// Enter or Space on the row under the cursor.
+    Activate(usize),
+    FocusNext,
+    FocusPrev,
+}
+
+/// Row cursor of a list, moved by the keyboard independently of the selection.
+#[derive(Debug, Default)]
+pub struct KeyboardNav {
+    cursor: Option<usize>,
+    typed: String,
+    typed_at: Option<Instant>,
+}
+
+impl KeyboardNav {
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<usize>) {
+        self.cursor = cursor;
+    }
+
+    /// Handle a key of a list of `len` rows, `label` gives the text of a row for type-ahead.
+    ///
+    /// Returns `None` for keys the list does not use.
+    pub fn key_down(
+        &mut self,
+        key: &KeyEvent,
+        len: usize,
+        label: impl Fn(usize) -> String,
+    ) -> Option<ListKey> {
+        self.key_down_at(key, len, label, Instant::now())
+    }
+
+    fn key_down_at(
+        &mut self,
+        key: &KeyEvent,
+        len: usize,
+        label: impl Fn(usize) -> String,
+        now: Instant,
+    ) -> Option<ListKey> {
+        match key.key {
+            KbKey::Tab if key.mods.shift() => return Some(ListKey::FocusPrev),
+            KbKey::Tab => return Some(ListKey::FocusNext),
+            _ => (),
+        }
+
+        if self
+            .typed_at
+            .map_or(true, |t| now.duration_since(t) > TYPE_AHEAD_TIMEOUT)
+        {
+            self.typed.clear();
+        }
+
+        let last = len.checked_sub(1)?;
+        let cursor = self.cursor.map(|c| c.min(last));
+
+        let target = match &key.key {
+            KbKey::Enter => return cursor.map(ListKey::Activate),
+            KbKey::Character(c) if c == " " && self.typed.is_empty() => {
+                return cursor.map(ListKey::Activate)
+            }
+            KbKey::ArrowDown | KbKey::ArrowRight => cursor.map_or(0, |c| (c + 1).min(last)),
+            KbKey::ArrowUp | KbKey::ArrowLeft => cursor.map_or(0, |c| c.saturating_sub(1)),
+            KbKey::PageDown => cursor.map_or(0, |c| (c + PAGE_ROWS).min(last)),
+            KbKey::PageUp => cursor.map_or(0, |c| c.saturating_sub(PAGE_ROWS)),
+            KbKey::Home => 0,
+            KbKey::End => last,
+            KbKey::Character(c) if !key.mods.ctrl() && !key.mods.alt() && !key.mods.meta() => {
+                self.typed.push_str(&c.to_lowercase());
+                self.typed_at = Some(now);
+                self.find_typed(cursor, len, label)?
+            }
+            _ => return None,
+        };
+
+        self.cursor = Some(target);
+        Some(ListKey::Move(target))
+    }
+
+    /// Row starting with the typed text, repeating one letter cycles through the rows starting with it.
+    fn find_typed(
+        &self,
+        cursor: Option<usize>,
+        len: usize,
+        label: impl Fn(usize) -> String,
+    ) -> Option<usize> {
+        let mut chars = self.typed.chars();
+        let first = chars.next()?;
+        let (prefix, start) = if chars.all(|c| c == first) {
+            (first.to_string(), cursor.map_or(0, |c| c + 1))
+        } else {
+            (self.typed.clone(), cursor.unwrap_or(0))
+        };
+
+        (0..len)
+            .map(|i| (start + i) % len)
+            .find(|&i| label(i).to_lowercase().starts_with(&prefix))
+    }
+}
+
+type FnOnActivate<T> = dyn Fn(&mut EventCtx, &mut T);
+type FnOnDelete<T> = dyn Fn(&mut EventCtx, &mut Vector<T>, usize);
+
+/// Keyboard navigation of a list with rows of the same height, like `KeyboardNav` in `NavList`.
+///
+/// The focused row is outlined, Enter or Space calls `on_activate` with it.
+pub struct RowNavigator<T: Clone, W> {
+    child: W,
+    row_height: f64,
+    nav: KeyboardNav,
+    on_activate: Box<FnOnActivate<T>>,
+    on_delete: Option<Box<FnOnDelete<T>>>,
+}
+
+impl<T: Data + Display, W: Widget<Vector<T>>> RowNavigator<T, W> {
+    pub fn new(
+        child: W,
+        row_height: f64,
+        on_activate: impl Fn(&mut EventCtx, &mut T) + 'static,
+    ) -> Self {
+        Self {
+            child,
+            row_height,
+            nav: KeyboardNav::default(),
+            on_activate: Box::new(on_activate),
+            on_delete: None,
+        }
+    }
+
+    /// Called on the Delete key with the rows and the focused row.
+    pub fn with_on_delete(
+        mut self,
+        on_delete: impl Fn(&mut EventCtx, &mut Vector<T>, usize) + 'static,
+    ) -> Self {
+        self.on_delete = Some(Box::new(on_delete));
+
+        self
+    }
+
+    fn row_rect(&self, row: usize, width: f64) -> Rect {
+        Rect::from_origin_size(
+            (0.0, row as f64 * self.row_height),
+            (width, self.row_height),
+        )
+    }
+}
+
+impl<T: Clone, W: Virtualized> Virtualized for RowNavigator<T, W> {
+    fn set_viewport(&mut self, viewport: Rect) -> Option<WidgetId> {
+        self.child.set_viewport(viewport)
+    }
+}
+
+impl<T: Data + Display, W: Widget<Vector<T>>> Widget<Vector<T>> for RowNavigator<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Vector<T>, env: &Env) {
+        self.child.event(ctx, event, data, env);
+
+        match event {
+            Event::MouseDown(mouse) => {
+                let row = (mouse.pos.y / self.row_height) as usize;
+                self.nav.set_cursor(Some(row).filter(|r| *r < data.len()));
+                ctx.request_focus();
+                ctx.request_paint();
+            }
+            Event::KeyDown(key) if !ctx.is_handled() => {
+                let cursor = self.nav.cursor().filter(|c| *c < data.len());
+                if let (KbKey::Delete, Some(on_delete), Some(cursor)) =
+                    (&key.key, &self.on_delete, cursor)
+                {
+                    on_delete(ctx, data, cursor);
+                    return ctx.set_handled();
+                }
+
+                match self.nav.key_down(key, data.len(), |i| data[i].to_string()) {
+                    Some(ListKey::Move(row)) => {
+                        let rect = self.row_rect(row, ctx.size().width);
+                        ctx.submit_notification(SCROLL_TO_VIEW.with(rect));
+                        ctx.request_paint();
+                    }
+                    Some(ListKey::Activate(row)) => (self.on_activate)(ctx, &mut data[row]),
+                    Some(ListKey::FocusNext) => ctx.focus_next(),
+                    Some(ListKey::FocusPrev) => ctx.focus_prev(),
+                    None => return,
+                }
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &Vector<T>,
+        env: &Env,
+    ) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+
+        self.child.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &Vector<T>, data: &Vector<T>, env: &Env) {
+        self.child.update(ctx, old_data, data, env)
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Vector<T>,
+        env: &Env,
+    ) -> Size {
+        self.child.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Vector<T>, env: &Env) {
+        self.child.paint(ctx, data, env);
+
+        if let Some(row) = self.nav.cursor().filter(|r| *r < data.len()) {
+            if ctx.is_focused() {
+                let rect = self
+                    .row_rect(row, ctx.size().width)
+                    .inset(-FOCUS_RING_WIDTH / 2.0);
+                ctx.stroke(rect, &env.get(theme::COLOR_ACCENT), FOCUS_RING_WIDTH);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn press(nav: &mut KeyboardNav, key: KbKey, labels: &[&str], now: Instant) -> Option<ListKey> {
+    nav.key_down_at(
+        &KeyEvent::for_test(druid::Modifiers::empty(), key),
+        labels.len(),
+        |i| labels[i].to_string(),
+        now,
+    )
+}
+
+#[test]
+fn test_keyboard_nav_moves() {
+    let labels = ["Arma 3", "Cities: Skylines", "Garry's Mod"];
+    let now = Instant::now();
+    let mut nav = KeyboardNav::default();
+
+    assert_eq!(
+        press(&mut nav, KbKey::ArrowDown, &labels, now),
+        Some(ListKey::Move(0))
+    );
+    assert_eq!(
+        press(&mut nav, KbKey::End, &labels, now),
+        Some(ListKey::Move(2))
+    );
+    assert_eq!(
+        press(&mut nav, KbKey::ArrowDown, &labels, now),
+        Some(ListKey::Move(2))
+    );
+    assert_eq!(
+        press(&mut nav, KbKey::PageUp, &labels, now),
+        Some(ListKey::Move(0))
+    );
+    assert_eq!(
+        press(&mut nav, KbKey::Enter, &labels, now),
+        Some(ListKey::Activate(0))
+    );
+    assert_eq!(press(&mut nav, KbKey::Escape, &labels, now), None);
+}
+
+#[test]
+fn test_keyboard_nav_type_ahead() {
+    let labels = ["Arma 3", "Cities: Skylines", "Cities XL", "Garry's Mod"];
+    let now = Instant::now();
+    let mut nav = KeyboardNav::default();
+    let typed = |nav: &mut KeyboardNav, c: &str, now| {
+        press(nav, KbKey::Character(c.to_string()), &labels, now)
+    };
+
+    assert_eq!(typed(&mut nav, "c", now), Some(ListKey::Move(1)));
+    assert_eq!(typed(&mut nav, "c", now), Some(ListKey::Move(2)));
+    assert_eq!(typed(&mut nav, "c", now), Some(ListKey::Move(1)));
+
+    let later = now + TYPE_AHEAD_TIMEOUT * 2;
+    assert_eq!(typed(&mut nav, "G", later), Some(ListKey::Move(3)));
+    assert_eq!(typed(&mut nav, "a", later), Some(ListKey::Move(3)));
+    assert_eq!(typed(&mut nav, "x", later), None);
+}
@@ -1,21 +1,29 @@
-use std::{cmp::Ordering, fmt::Display};
+use std::{collections::BTreeMap, fmt::Display};
 
 use druid::{
-    widget::{Axis, Flex, Label, ListIter, Widget},
-    BoxConstraints, Color, Data, Env, EventCtx, KeyOrValue, LifeCycle, Point, Rect, RenderContext,
-    Selector, Size, UnitPoint, WidgetExt, WidgetPod,
+    widget::{Flex, Label, ListIter, Widget},
+    BoxConstraints, Data, Event, EventCtx, LifeCycle, Point, Rect, RenderContext, Selector, Size,
+    UnitPoint, WidgetExt, WidgetId, WidgetPod,
 };
 
 use crate::ui;
+use crate::widget::keyboard::{KeyboardNav, ListKey, SCROLL_TO_VIEW};
+use crate::widget::virtual_list::{sync_row_pods, RowPod, RowRange, Virtualized, SYNC_ROWS};
+
+const FOCUS_RING_WIDTH: f64 = 2.0;
 
 type FnOnSelected<C> = dyn Fn(&mut EventCtx, &C);
 
+/// List of selectable items with rows of `NAV_LIST_ITEM_HEIGHT`.
+///
+/// Only rows visible in the `VirtualScroll` around the list are built.
 pub struct NavList<C> {
     item_builder: Box<dyn Fn() -> Box<dyn Widget<(C, bool)>>>,
     on_selected: Box<FnOnSelected<C>>,
-    children: Vec<WidgetPod<(C, bool), Box<dyn Widget<(C, bool)>>>>,
-    axis: Axis,
-    spacing: KeyOrValue<f64>,
+    children: BTreeMap<usize, RowPod<(C, bool)>>,
+    rows: RowRange,
+    nav: KeyboardNav,
+    auto_focus: bool,
 }
 
 impl<C: Data + Display> NavList<C> {
@@ -25,48 +33,141 @@ impl<C: Data + Display> NavList<C> {
         Self {
             item_builder: Box::new(move || Box::new(item_builder())),
             on_selected: Box::new(on_selected),
-            children: Vec::new(),
-            axis: Axis::Vertical,
-            spacing: KeyOrValue::Concrete(0.),
+            children: BTreeMap::new(),
+            rows: RowRange::new(ui::theme::NAV_LIST_ITEM_HEIGHT),
+            nav: KeyboardNav::default(),
+            auto_focus: false,
         }
     }
 
+    /// Take keyboard focus when the list is shown.
+    pub fn with_auto_focus(mut self, auto_focus: bool) -> Self {
+        self.auto_focus = auto_focus;
+
+        self
+    }
+
     const SELECTOR_ON_CLICK: Selector<C> = Selector::new("naw-item.on-click");
+    const SELECTOR_TAKE_FOCUS: Selector = Selector::new("nav-list.take-focus");
 
-    /// When the widget is created or the data changes, create or remove children as needed
+    /// When the widget is created, the data or the viewport changes, create or remove children as needed
     ///
     /// Returns `true` if children were added or removed.
-    fn update_child_count(&mut self, data: &impl ListIter<C>, _env: &Env) -> bool {
-        let len = self.children.len();
-        match len.cmp(&data.data_len()) {
-            Ordering::Greater => self.children.truncate(data.data_len()),
-            Ordering::Less => data.for_each(|_, i| {
-                if i >= len {
-                    let child = WidgetPod::new((self.item_builder)());
-                    self.children.push(child);
+    fn update_children(&mut self, data: &impl ListIter<C>) -> bool {
+        let rows = self.rows.rows(data.data_len());
+
+        sync_row_pods(&mut self.children, rows, &self.item_builder)
+    }
+
+    fn index_of(data: &impl ListIter<C>, item: &C) -> Option<usize> {
+        let mut index = None;
+        data.for_each(|child_data, i| {
+            if index.is_none() && child_data.same(item) {
+                index = Some(i);
+            }
+        });
+
+        index
+    }
+
+    /// Data of the built children with their row, the bool is `true` for the selected item.
+    fn children_data(
+        data: &impl ListIter<C>,
+        selected_item: &Option<C>,
+        rows: &BTreeMap<usize, RowPod<(C, bool)>>,
+    ) -> Vec<(usize, (C, bool))> {
+        let mut children_data = Vec::with_capacity(rows.len());
+        data.for_each(|child_data, i| {
+            if rows.contains_key(&i) {
+                let selected = if let Some(selected_item) = selected_item {
+                    child_data.same(selected_item)
+                } else {
+                    false
+                };
+
+                children_data.push((i, (child_data.clone(), selected)));
+            }
+        });
+
+        children_data
+    }
+
+    /// What the list does with the event, `None` if it is left to the items.
+    fn react(
+        &mut self,
+        event: &Event,
+        data: &impl ListIter<C>,
+        selected_item: &Option<C>,
+    ) -> Option<NavListAction<C>> {
+        match event {
+            Event::Notification(notification) => {
+                let item = notification.get(Self::SELECTOR_ON_CLICK)?;
+                self.nav.set_cursor(Self::index_of(data, item));
+
+                Some(NavListAction::Select(item.clone()))
+            }
+            Event::Command(cmd) if cmd.is(Self::SELECTOR_TAKE_FOCUS) => {
+                Some(NavListAction::TakeFocus)
+            }
+            Event::Command(cmd) if cmd.is(SYNC_ROWS) => {
+                Some(NavListAction::SyncRows(self.update_children(data)))
+            }
+            Event::KeyDown(key) => {
+                let mut all_data = Vec::with_capacity(data.data_len());
+                data.for_each(|child_data, _| all_data.push(child_data.clone()));
+                if self.nav.cursor().is_none() {
+                    let selected = selected_item
+                        .as_ref()
+                        .and_then(|s| all_data.iter().position(|c| c.same(s)));
+                    self.nav.set_cursor(selected);
                 }
-            }),
-            Ordering::Equal => (),
+
+                let key = self
+                    .nav
+                    .key_down(key, all_data.len(), |i| all_data[i].to_string())?;
+                Some(match key {
+                    ListKey::Move(i) => NavListAction::ScrollTo(i),
+                    ListKey::Activate(i) => NavListAction::Select(all_data[i].clone()),
+                    ListKey::FocusNext => NavListAction::FocusNext,
+                    ListKey::FocusPrev => NavListAction::FocusPrev,
+                })
+            }
+            _ => None,
         }
-        len != data.data_len()
     }
 
-    fn axis_constraints(
-        axis: &Axis,
-        bc: &BoxConstraints,
-        min_major: f64,
-        major: f64,
-    ) -> BoxConstraints {
-        match axis {
-            Axis::Horizontal => BoxConstraints::new(
-                Size::new(min_major, bc.min().height),
-                Size::new(major, bc.max().height),
-            ),
-            Axis::Vertical => BoxConstraints::new(
-                Size::new(bc.min().width, min_major),
-                Size::new(bc.max().width, major),
-            ),
-        }
+    /// Constraints of every row, as wide as the list.
+    fn row_constraints(&self, bc: &BoxConstraints) -> BoxConstraints {
+        BoxConstraints::tight(Size::new(bc.max().width, self.rows.row_height()))
+    }
+
+    fn row_origin(&self, row: usize) -> Point {
+        Point::new(0., row as f64 * self.rows.row_height())
+    }
+
+    /// Size of the list of `len` rows, all of them, not only the built ones.
+    fn list_size(&self, bc: &BoxConstraints, len: usize) -> Size {
+        bc.constrain(Size::new(bc.max().width, self.rows.height(len)))
+    }
+}
+
+/// What an event asks the list to do.
+#[derive(Debug, PartialEq)]
+enum NavListAction<C> {
+    /// The item was clicked or activated with the keyboard.
+    Select(C),
+    TakeFocus,
+    /// Rows were synced with the viewport, `true` if children were added or removed.
+    SyncRows(bool),
+    /// The keyboard cursor moved to the row.
+    ScrollTo(usize),
+    FocusNext,
+    FocusPrev,
+}
+
+impl<C> Virtualized for NavList<C> {
+    fn set_viewport(&mut self, viewport: Rect) -> Option<WidgetId> {
+        self.rows.set_viewport(viewport)
     }
 }
 
@@ -84,30 +185,49 @@ where
         (items, selected_item): &mut NavListData<C, I>,
         env: &druid::Env,
     ) {
-        match event {
-            druid::Event::Notification(notification) => {
-                if let Some(payload) = notification.get(Self::SELECTOR_ON_CLICK) {
-                    (self.on_selected)(ctx, payload);
-                    return ctx.set_handled();
+        let data = items.as_mut().unwrap();
+
+        match self.react(event, data, selected_item) {
+            Some(NavListAction::Select(item)) => {
+                ctx.request_focus();
+                (self.on_selected)(ctx, &item);
+                return ctx.set_handled();
+            }
+            Some(NavListAction::TakeFocus) => {
+                ctx.request_focus();
+                return ctx.set_handled();
+            }
+            Some(NavListAction::SyncRows(changed)) => {
+                if changed {
+                    ctx.children_changed();
+                    ctx.request_layout();
                 }
+                return ctx.set_handled();
             }
-            _ => (),
+            Some(NavListAction::ScrollTo(i)) => {
+                let rect = self.rows.row_rect(i, ctx.size().width);
+                ctx.submit_notification(SCROLL_TO_VIEW.with(rect));
+                ctx.request_paint();
+                return ctx.set_handled();
+            }
+            Some(NavListAction::FocusNext) => {
+                ctx.focus_next();
+                return ctx.set_handled();
+            }
+            Some(NavListAction::FocusPrev) => {
+                ctx.focus_prev();
+                return ctx.set_handled();
+            }
+            // items have no keyboard handling of their own
+            None if matches!(event, Event::KeyDown(_)) => return,
+            None => (),
         }
 
-        let data = items.as_mut().unwrap();
-
-        let mut children = self.children.iter_mut();
-        data.for_each_mut(|child_data, _| {
-            if let Some(child) = children.next() {
-                let selected = if let Some(selected_item) = selected_item {
-                    child_data.same(selected_item)
-                } else {
-                    false
-                };
-
-                child.event(ctx, event, &mut (child_data.clone(), selected), env);
+        for (i, mut child_data) in Self::children_data(data, selected_item, &self.children) {
+            if let Some(child) = self.children.get_mut(&i) {
+                child.event(ctx, event, &mut child_data, env);
             }
-        });
+        }
     }
 
     fn lifecycle(
@@ -119,53 +239,47 @@ where
     ) {
         let data = items.as_ref().unwrap();
 
-        if let LifeCycle::WidgetAdded = event {
-            if self.update_child_count(data, env) {
-                ctx.children_changed();
+        match event {
+            LifeCycle::WidgetAdded => {
+                self.rows.set_id(ctx.widget_id());
+                ctx.register_for_focus();
+                if self.auto_focus {
+                    ctx.submit_command(Self::SELECTOR_TAKE_FOCUS.to(ctx.widget_id()));
+                }
+                if self.update_children(data) {
+                    ctx.children_changed();
+                }
             }
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
         }
 
-        let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
-            if let Some(child) = children.next() {
-                let selected = if let Some(selected_item) = selected_item {
-                    child_data.same(selected_item)
-                } else {
-                    false
-                };
-
-                child.lifecycle(ctx, event, &(child_data.clone(), selected), env);
+        for (i, child_data) in Self::children_data(data, selected_item, &self.children) {
+            if let Some(child) = self.children.get_mut(&i) {
+                child.lifecycle(ctx, event, &child_data, env);
             }
-        });
+        }
     }
 
     fn update(
         &mut self,
         ctx: &mut druid::UpdateCtx,
-        old_data: &NavListData<C, I>,
-        data: &NavListData<C, I>,
+        _old_data: &NavListData<C, I>,
+        (items, selected_item): &NavListData<C, I>,
         env: &druid::Env,
     ) {
-        let selected_item = data.1.as_ref();
-        let data = data.0.as_ref().unwrap();
+        let data = items.as_ref().unwrap();
 
         // we send update to children first, before adding or removing children;
         // this way we avoid sending update to newly added children, at the cost
         // of potentially updating children that are going to be removed.
-        let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
-            if let Some(child) = children.next() {
-                let selected = if let Some(selected_item) = selected_item {
-                    child_data.same(selected_item)
-                } else {
-                    false
-                };
-
-                child.update(ctx, &(child_data.clone(), selected), env);
+        for (i, child_data) in Self::children_data(data, selected_item, &self.children) {
+            if let Some(child) = self.children.get_mut(&i) {
+                child.update(ctx, &child_data, env);
             }
-        });
+        }
 
-        if self.update_child_count(data, env) {
+        if self.update_children(data) {
             ctx.children_changed();
         }
     }
@@ -179,42 +293,16 @@ where
     ) -> druid::Size {
         let data = items.as_ref().unwrap();
 
-        let axis = self.axis;
-        let spacing = self.spacing.resolve(env);
-        let mut minor = axis.minor(bc.min());
-        let mut major_pos = 0.0;
-        let mut paint_rect = Rect::ZERO;
-        let mut children = self.children.iter_mut();
-        let child_bc = Self::axis_constraints(&axis, bc, 0., f64::INFINITY);
-        data.for_each(|child_data, _| {
-            let child = match children.next() {
-                Some(child) => child,
-                None => {
-                    return;
-                }
-            };
-
-            let selected = if let Some(selected_item) = selected_item {
-                child_data.same(selected_item)
-            } else {
-                false
-            };
-
-            let child_size = child.layout(ctx, &child_bc, &(child_data.clone(), selected), env);
-            let child_pos: Point = axis.pack(major_pos, 0.).into();
-            child.set_origin(ctx, &(child_data.clone(), selected), env, child_pos);
-            paint_rect = paint_rect.union(child.paint_rect());
-            minor = minor.max(axis.minor(child_size));
-            major_pos += axis.major(child_size) + spacing;
-        });
-
-        // correct overshoot at end.
-        major_pos -= spacing;
+        let child_bc = self.row_constraints(bc);
+        for (i, child_data) in Self::children_data(data, selected_item, &self.children) {
+            let origin = self.row_origin(i);
+            if let Some(child) = self.children.get_mut(&i) {
+                child.layout(ctx, &child_bc, &child_data, env);
+                child.set_origin(ctx, &child_data, env, origin);
+            }
+        }
 
-        let my_size = bc.constrain(Size::from(axis.pack(major_pos, minor)));
-        let insets = paint_rect - my_size.to_rect();
-        ctx.set_paint_insets(insets);
-        my_size
+        self.list_size(bc, data.data_len())
     }
 
     fn paint(
@@ -225,18 +313,20 @@ where
     ) {
         let data = items.as_ref().unwrap();
 
-        let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
-            if let Some(child) = children.next() {
-                let selected = if let Some(selected_item) = selected_item {
-                    child_data.same(selected_item)
-                } else {
-                    false
-                };
-
-                child.paint(ctx, &(child_data.clone(), selected), env);
+        for (i, child_data) in Self::children_data(data, selected_item, &self.children) {
+            if let Some(child) = self.children.get_mut(&i) {
+                child.paint(ctx, &child_data, env);
             }
-        });
+        }
+
+        let cursor = self.nav.cursor().filter(|i| *i < data.data_len());
+        if let (Some(cursor), true) = (cursor, ctx.is_focused()) {
+            let rect = self
+                .rows
+                .row_rect(cursor, ctx.size().width)
+                .inset(-FOCUS_RING_WIDTH / 2.0);
+            ctx.stroke(rect, &env.get(ui::theme::COLOR_ACCENT), FOCUS_RING_WIDTH);
+        }
     }
 }
 
@@ -267,6 +357,22 @@ impl<T: Data + Display> NavListItem<T> {
 
         WidgetPod::new(Box::new(item))
     }
+
+    /// Mark the item as selected or not, returns `true` if the inner item was rebuilt.
+    fn set_focus(&mut self, focus: bool) -> bool {
+        if self.focus == focus {
+            return false;
+        }
+
+        self.focus = focus;
+        self.inner = Some(Self::build_inner_item());
+        true
+    }
+
+    /// Whether the event selects the item, the list is notified of it.
+    fn clicked(event: &Event) -> bool {
+        matches!(event, Event::MouseDown(_))
+    }
 }
 
 impl<I: Data + Display> Widget<(I, bool)> for NavListItem<I> {
@@ -277,11 +383,8 @@ impl<I: Data + Display> Widget<(I, bool)> for NavListItem<I> {
         (data, _): &mut (I, bool),
         env: &druid::Env,
     ) {
-        match event {
-            druid::Event::MouseDown(_) => {
-                ctx.submit_notification(NavList::SELECTOR_ON_CLICK.with(data.clone()));
-            }
-            _ => (),
+        if Self::clicked(event) {
+            ctx.submit_notification(NavList::SELECTOR_ON_CLICK.with(data.clone()));
         }
 
         if let Some(child) = self.inner.as_mut() {
@@ -318,9 +421,7 @@ impl<I: Data + Display> Widget<(I, bool)> for NavListItem<I> {
         (data, focus): &(I, bool),
         env: &druid::Env,
     ) {
-        if self.focus != *focus {
-            self.focus = *focus;
-            self.inner = Some(Self::build_inner_item());
+        if self.set_focus(*focus) {
             ctx.children_changed();
         // Because the new child has not yet been initialized, we have to skip the update after switching.
         } else if let Some(child) = self.inner.as_mut() {
@@ -349,7 +450,7 @@ impl<I: Data + Display> Widget<(I, bool)> for NavListItem<I> {
         if let Some(child) = self.inner.as_mut() {
             if ctx.is_hot() {
                 let rect = ctx.size().to_rect();
-                ctx.fill(rect, &Color::GRAY);
+                ctx.fill(rect, &env.get(ui::theme::COLOR_SURFACE_HOT));
             }
             if self.focus {
                 let rect = ctx.size().to_rect();
@@ -357,10 +458,139 @@ impl<I: Data + Display> Widget<(I, bool)> for NavListItem<I> {
                     height: rect.height(),
                     width: ui::theme::NAV_LIST_ITEM_PADDING / 2.,
                 });
-                ctx.fill(bar, &Color::WHITE);
+                ctx.fill(bar, &env.get(ui::theme::COLOR_ACCENT));
             }
 
             child.paint_raw(ctx, data, env);
         }
     }
 }
+
+#[cfg(test)]
+fn sample_apps(len: usize) -> druid::im::Vector<String> {
+    (0..len).map(|i| format!("App {}", i)).collect()
+}
+
+#[test]
+fn test_nav_list_builds_visible_rows() {
+    let apps = sample_apps(1000);
+    let mut list = NavList::new(|_, _: &String| ());
+
+    assert!(list.update_children(&apps));
+    assert_eq!(list.children.len(), 30);
+
+    // rows 40 to 48 are visible, a few more are built around them
+    let top = 40.0 * ui::theme::NAV_LIST_ITEM_HEIGHT;
+    list.set_viewport(Rect::new(0.0, top, 200.0, top + 400.0));
+    assert!(list.update_children(&apps));
+    assert_eq!(list.children.keys().next(), Some(&35));
+    assert_eq!(list.children.keys().last(), Some(&52));
+    assert!(!list.update_children(&apps));
+
+    assert!(list.update_children(&sample_apps(45)));
+    assert_eq!(list.children.keys().last(), Some(&44));
+    assert_eq!(list.rows.height(45), 45.0 * ui::theme::NAV_LIST_ITEM_HEIGHT);
+}
+
+#[test]
+fn test_nav_list_selection() {
+    let apps = sample_apps(5);
+    let mut list = NavList::new(|_, _: &String| ());
+    list.update_children(&apps);
+
+    let children_data = NavList::children_data(&apps, &Some(apps[2].clone()), &list.children);
+    let selected: Vec<usize> = children_data
+        .iter()
+        .filter(|(_, (_, selected))| *selected)
+        .map(|(i, _)| *i)
+        .collect();
+    assert_eq!(children_data.len(), 5);
+    assert_eq!(selected, vec![2]);
+
+    let unselected = NavList::children_data(&apps, &None, &list.children);
+    assert!(unselected.iter().all(|(_, (_, selected))| !selected));
+
+    assert_eq!(NavList::index_of(&apps, &apps[3]), Some(3));
+    assert_eq!(NavList::index_of(&apps, &"Other".to_string()), None);
+}
+
+#[test]
+fn test_nav_list_item_rebuilds_on_focus() {
+    let mut item = NavListItem::<String>::new();
+    assert!(item.inner.is_none());
+
+    assert!(item.set_focus(true));
+    assert!(item.inner.is_some());
+    assert!(!item.set_focus(true));
+    assert!(item.set_focus(false));
+}
+
+#[test]
+fn test_nav_list_events() {
+    use crate::widget::harness::{click, command, key_down, mouse_move};
+    use druid::KbKey;
+
+    let apps = sample_apps(1000);
+    let selected = Some(apps[2].clone());
+    let mut list = NavList::new(|_, _: &String| ());
+    let react = |list: &mut NavList<String>, event| list.react(&event, &apps, &selected);
+
+    assert_eq!(
+        react(&mut list, command(NavList::<String>::SELECTOR_TAKE_FOCUS)),
+        Some(NavListAction::TakeFocus)
+    );
+    assert_eq!(
+        react(&mut list, command(SYNC_ROWS)),
+        Some(NavListAction::SyncRows(true))
+    );
+    assert_eq!(
+        react(&mut list, command(SYNC_ROWS)),
+        Some(NavListAction::SyncRows(false))
+    );
+
+    // the cursor starts at the selected item
+    assert_eq!(
+        react(&mut list, key_down(KbKey::ArrowDown)),
+        Some(NavListAction::ScrollTo(3))
+    );
+    assert_eq!(
+        react(&mut list, key_down(KbKey::Enter)),
+        Some(NavListAction::Select(apps[3].clone()))
+    );
+    assert_eq!(
+        react(&mut list, key_down(KbKey::Tab)),
+        Some(NavListAction::FocusNext)
+    );
+    assert_eq!(react(&mut list, key_down(KbKey::Escape)), None);
+
+    // clicks go to the items, they notify the list
+    assert_eq!(react(&mut list, click(Point::new(10.0, 10.0))), None);
+    assert!(NavListItem::<String>::clicked(&click(Point::new(
+        10.0, 10.0
+    ))));
+    assert!(!NavListItem::<String>::clicked(&mouse_move(Point::new(
+        10.0, 10.0
+    ))));
+}
+
+#[test]
+fn test_nav_list_layout() {
+    use crate::widget::harness::window;
+
+    let list = NavList::new(|_, _: &String| ());
+    let height = ui::theme::NAV_LIST_ITEM_HEIGHT;
+
+    assert_eq!(
+        list.list_size(&window(200.0, 1000.0), 5),
+        Size::new(200.0, 5.0 * height)
+    );
+    assert_eq!(
+        list.list_size(&window(200.0, 400.0), 1000),
+        Size::new(200.0, 400.0)
+    );
+
+    let row_bc = list.row_constraints(&window(200.0, 400.0));
+    assert_eq!(row_bc.min(), Size::new(200.0, height));
+    assert_eq!(row_bc.max(), Size::new(200.0, height));
+    assert_eq!(list.row_origin(3), Point::new(0.0, 3.0 * height));
+}
@@ -1,3 +1,10 @@
+pub mod dialog;
+#[cfg(test)]
+mod harness;
+pub mod keyboard;
 pub mod list;
+pub mod notification;
 pub mod spinner;
 pub mod stacked_container;
+pub mod treemap;
+pub mod virtual_list;
@@ -0,0 +1,113 @@
+use chrono::{Local, TimeZone};
+use druid::widget::{
+    Button, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, Painter, Scroll, SizedBox,
+};
+use druid::{Color, RenderContext, Widget, WidgetExt};
+
+use crate::{
+    cmd,
+    data::{AppState, Notification, Notifications, Severity},
+    ui::theme,
+};
+
+const LOG_PANEL_HEIGHT: f64 = 160.0;
+const SEVERITY_MARK_WIDTH: f64 = 4.0;
+
+pub fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Info => theme::COLOR_INFO,
+        Severity::Warning => theme::COLOR_WARNING,
+        Severity::Error => theme::COLOR_ERROR,
+    }
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "Info",
+        Severity::Warning => "Warning",
+        Severity::Error => "Error",
+    }
+}
+
+fn format_time(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(t) => t.format("%H:%M:%S").to_string(),
+        None => String::new(),
+    }
+}
+
+/// Banners above the content and the log panel below it, toggled by `Notifications.log_open`.
+pub fn notification_host(content: impl Widget<AppState> + 'static) -> impl Widget<AppState> {
+    let toasts = List::new(toast_widget).lens(Notifications::toasts);
+    let log = Either::new(
+        |data: &Notifications, _| data.log_open,
+        log_panel_widget(),
+        Flex::column(),
+    );
+
+    Flex::column()
+        .with_child(toasts.lens(AppState::notifications))
+        .with_flex_child(content, 1.0)
+        .with_child(log.lens(AppState::notifications))
+}
+
+/// Banner with severity, message and a button to dismiss it.
+fn toast_widget() -> impl Widget<Notification> {
+    let mark = SizedBox::empty()
+        .width(SEVERITY_MARK_WIDTH)
+        .expand_height()
+        .background(Painter::new(|ctx, n: &Notification, _| {
+            let rect = ctx.size().to_rect();
+            ctx.fill(rect, &severity_color(n.severity));
+        }));
+    let message = Label::new(|n: &Notification, _env: &_| {
+        format!("{}: {}", severity_label(n.severity), n.message)
+    })
+    .with_line_break_mode(LineBreaking::WordWrap);
+
+    Flex::row()
+        .with_child(mark)
+        .with_spacer(theme::grid(1.0))
+        .with_flex_child(message.expand_width(), 1.0)
+        .with_child(
+            Button::new("Dismiss").on_click(|ctx, n: &mut Notification, _| {
+                ctx.submit_command(cmd::DISMISS_NOTIFICATION.with(n.id))
+            }),
+        )
+        .padding(theme::grid(0.5))
+        .background(theme::COLOR_SURFACE)
+        .fix_height(theme::NAV_LIST_ITEM_HEIGHT)
+}
+
+/// Recent events with their time, newest at the bottom.
+fn log_panel_widget() -> impl Widget<Notifications> {
+    let entry = || {
+        Label::new(|n: &Notification, _env: &_| {
+            format!(
+                "{}  {:<7}  {}",
+                format_time(n.time),
+                severity_label(n.severity),
+                n.message
+            )
+        })
+        .with_text_size(12.0)
+        .with_text_color(theme::COLOR_TEXT_MUTED)
+        .align_left()
+    };
+    let log = Scroll::new(List::new(entry).lens(Notifications::log)).vertical();
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Flex::row()
+                .with_child(Label::new("Log"))
+                .with_flex_spacer(1.0)
+                .with_child(
+                    Button::new("Close")
+                        .on_click(|_, data: &mut Notifications, _| data.log_open = false),
+                ),
+        )
+        .with_flex_child(log.expand_width(), 1.0)
+        .padding(theme::grid(1.0))
+        .fix_height(LOG_PANEL_HEIGHT)
+}
@@ -1,8 +1,8 @@
 use std::f64::consts::PI;
 
 use druid::{
-    kurbo::Circle, BoxConstraints, Color, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
-    LifeCycleCtx, PaintCtx, RenderContext, Size, UpdateCtx, Vec2, Widget, WidgetExt,
+    kurbo::Circle, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, RenderContext, Size, UpdateCtx, Vec2, Widget, WidgetExt,
 };
 
 use crate::ui::theme;
@@ -16,15 +16,39 @@ impl Spinner {
     pub fn new() -> Self {
         Self { t: 0.0 }
     }
+
+    /// Advance the animation by `interval` nanoseconds, one turn takes a second.
+    fn advance(&mut self, interval: u64) {
+        self.t += (interval as f64) * 1e-9;
+        if self.t >= 1.0 {
+            self.t = 0.0;
+        }
+    }
+
+    /// Advance the animation on animation frames, returns `true` if it moved.
+    fn animate(&mut self, event: &Event) -> bool {
+        match event {
+            Event::AnimFrame(interval) => {
+                self.advance(*interval);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Dot drawn highlighted, from 1 to 6.
+    fn active_dot(&self) -> i32 {
+        7 - (1 + (6.0 * self.t).floor() as i32)
+    }
+}
+
+fn spinner_size(bc: &BoxConstraints) -> Size {
+    bc.constrain(Size::new(theme::grid(6.0), theme::grid(6.0)))
 }
 
 impl<T: Data> Widget<T> for Spinner {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, _env: &Env) {
-        if let Event::AnimFrame(interval) = event {
-            self.t += (*interval as f64) * 1e-9;
-            if self.t >= 1.0 {
-                self.t = 0.0;
-            }
+        if self.animate(event) {
             ctx.request_anim_frame();
             ctx.request_paint();
         }
@@ -46,14 +70,14 @@ impl<T: Data> Widget<T> for Spinner {
         _data: &T,
         _env: &Env,
     ) -> Size {
-        bc.constrain(Size::new(theme::grid(6.0), theme::grid(6.0)))
+        spinner_size(bc)
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, _data: &T, env: &Env) {
         let center = ctx.size().to_rect().center();
-        let c0 = theme::COLOR_GREY_400;
-        let c1 = Color::WHITE;
-        let active = 7 - (1 + (6.0 * self.t).floor() as i32);
+        let c0 = env.get(theme::COLOR_TEXT_MUTED);
+        let c1 = env.get(theme::COLOR_ACCENT);
+        let active = self.active_dot();
         for i in 1..=6 {
             let step = f64::from(i);
             let angle = Vec2::from_angle((step / 6.0) * -2.0 * PI);
@@ -71,3 +95,40 @@ impl<T: Data> Widget<T> for Spinner {
 pub fn spinner_widget<T: Data>() -> impl Widget<T> {
     Spinner::new().center()
 }
+
+#[test]
+fn test_spinner_animation() {
+    let mut spinner = Spinner::new();
+    assert_eq!(spinner.active_dot(), 6);
+
+    spinner.advance(550_000_000);
+    assert_eq!(spinner.active_dot(), 3);
+
+    spinner.advance(350_000_000);
+    assert_eq!(spinner.active_dot(), 1);
+
+    spinner.advance(200_000_000);
+    assert_eq!(spinner.active_dot(), 6);
+}
+
+#[test]
+fn test_spinner_anim_frames() {
+    use crate::widget::harness::click;
+
+    let mut spinner = Spinner::new();
+    assert!(spinner.animate(&Event::AnimFrame(550_000_000)));
+    assert_eq!(spinner.active_dot(), 3);
+
+    assert!(!spinner.animate(&click(druid::Point::ORIGIN)));
+    assert_eq!(spinner.active_dot(), 3);
+}
+
+#[test]
+fn test_spinner_size() {
+    use crate::widget::harness::window;
+
+    assert_eq!(spinner_size(&window(400.0, 400.0)), Size::new(48.0, 48.0));
+
+    let tight = BoxConstraints::tight(Size::new(20.0, 30.0));
+    assert_eq!(spinner_size(&tight), Size::new(20.0, 30.0));
+}
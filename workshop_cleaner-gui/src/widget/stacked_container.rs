@@ -46,6 +46,24 @@ impl<T: Data> StackedContainer<T> {
 
         self
     }
+
+    /// Whether the bottom and the top widget get an event for the data.
+    ///
+    /// The top widget is painted only while it gets events.
+    fn route_event(&mut self, data: &T) -> (bool, bool) {
+        self.overlay_shown = (self.show_overlay_callback)(data);
+        let bottom = !self.overlay_shown || (self.passthrough_overlay_events_callback)(data);
+
+        (bottom, self.overlay_shown)
+    }
+}
+
+/// Size of the container, large enough for both widgets.
+fn stacked_size(bottom: Size, top: Size) -> Size {
+    Size {
+        width: bottom.width.max(top.width),
+        height: bottom.height.max(top.height),
+    }
 }
 
 // TODO: callback condition to render top element, callback condition to pass events to bottom element
@@ -57,11 +75,11 @@ impl<T: Data> Widget<T> for StackedContainer<T> {
         data: &mut T,
         env: &druid::Env,
     ) {
-        self.overlay_shown = (self.show_overlay_callback)(data);
-        if !self.overlay_shown || (self.passthrough_overlay_events_callback)(data) {
+        let (bottom, top) = self.route_event(data);
+        if bottom {
             self.inner_bottom.event(ctx, event, data, env);
         }
-        if self.overlay_shown {
+        if top {
             self.inner_top.event(ctx, event, data, env);
         }
     }
@@ -95,10 +113,7 @@ impl<T: Data> Widget<T> for StackedContainer<T> {
         self.inner_bottom.set_origin(ctx, data, env, Point::ORIGIN);
         self.inner_top.set_origin(ctx, data, env, Point::ORIGIN);
 
-        Size {
-            width: bottom_layout.width.max(top_layout.width),
-            height: bottom_layout.height.max(top_layout.height),
-        }
+        stacked_size(bottom_layout, top_layout)
     }
 
     fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &T, env: &druid::Env) {
@@ -108,3 +123,42 @@ impl<T: Data> Widget<T> for StackedContainer<T> {
         }
     }
 }
+
+#[cfg(test)]
+fn overlay_container() -> StackedContainer<(bool, bool)> {
+    StackedContainer::new(
+        druid::widget::Label::new("bottom"),
+        druid::widget::Label::new("top"),
+    )
+    .with_show_overlay_callback(Box::new(|(shown, _)| *shown))
+    .with_passthrough_overlay_events_callback(Box::new(|(_, passthrough)| *passthrough))
+}
+
+#[test]
+fn test_stacked_container_route_event() {
+    let mut container = overlay_container();
+
+    assert_eq!(container.route_event(&(false, false)), (true, false));
+    assert!(!container.overlay_shown);
+    assert_eq!(container.route_event(&(true, false)), (false, true));
+    assert!(container.overlay_shown);
+    assert_eq!(container.route_event(&(true, true)), (true, true));
+}
+
+#[test]
+fn test_stacked_container_default_callbacks() {
+    let mut container = StackedContainer::new(
+        druid::widget::Label::new("bottom"),
+        druid::widget::Label::new("top"),
+    );
+
+    assert_eq!(container.route_event(&()), (true, false));
+}
+
+#[test]
+fn test_stacked_size() {
+    assert_eq!(
+        stacked_size(Size::new(400.0, 100.0), Size::new(200.0, 300.0)),
+        Size::new(400.0, 300.0)
+    );
+}
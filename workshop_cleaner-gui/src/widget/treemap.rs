@@ -0,0 +1,387 @@
+use druid::{
+    im::Vector, BoxConstraints, Color, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, Point, Rect, RenderContext, Size, TextLayout, UpdateCtx, Widget,
+};
+use humansize::FileSize;
+use workshop_cleaner_core::cleaner::ItemState;
+
+use crate::{
+    cmd,
+    data::{SteamApp, SteamWorkshopItem},
+    ui::theme,
+};
+
+/// Blocks smaller than this are drawn without a label.
+const LABEL_MIN_SIZE: Size = Size::new(60.0, 20.0);
+const BAR_HEIGHT: f64 = 24.0;
+/// Free space thinner than this is not divided any further, blocks left get no area.
+const MIN_SIDE: f64 = 1e-6;
+
+pub fn state_color(state: ItemState) -> Color {
+    match state {
+        ItemState::Unsubscribed => theme::COLOR_STATE_UNSUBSCRIBED,
+        ItemState::Pinned => theme::COLOR_STATE_PINNED,
+        ItemState::InUse => theme::COLOR_STATE_IN_USE,
+        ItemState::UnknownFolder => theme::COLOR_STATE_UNKNOWN,
+    }
+}
+
+/// Squarified treemap layout, each size gets a rectangle of proportional area.
+///
+/// Sizes have to be positive and sorted from the largest, rectangles are returned in the same order.
+pub fn squarify(sizes: &[f64], rect: Rect) -> Vec<Rect> {
+    let total: f64 = sizes.iter().sum();
+    if total <= 0.0 || rect.area() <= 0.0 {
+        return vec![Rect::ZERO; sizes.len()];
+    }
+
+    let scale = rect.area() / total;
+    let areas: Vec<f64> = sizes.iter().map(|s| s * scale).collect();
+    let mut rects = Vec::with_capacity(areas.len());
+    let mut free = rect;
+    let mut start = 0;
+
+    while start < areas.len() {
+        let side = free.width().min(free.height());
+        if side <= MIN_SIDE {
+            // rounding left no space for the smallest blocks
+            let empty = Rect::from_origin_size(free.origin(), Size::ZERO);
+            rects.resize(areas.len(), empty);
+            break;
+        }
+
+        // add blocks to the row while it makes the worst aspect ratio better
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst_ratio(&areas[start..=end], side) <= worst_ratio(&areas[start..end], side)
+        {
+            end += 1;
+        }
+
+        free = layout_row(&areas[start..end], free, &mut rects);
+        start = end;
+    }
+
+    rects
+}
+
+fn worst_ratio(row: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+
+    (side * side * max / (sum * sum)).max(sum * sum / (side * side * min))
+}
+
+/// Place the row along the shorter side of the free space, returns space left.
+fn layout_row(row: &[f64], free: Rect, rects: &mut Vec<Rect>) -> Rect {
+    let sum: f64 = row.iter().sum();
+
+    // rounding may make the row a bit longer or thicker than the free space
+    if free.width() >= free.height() {
+        let width = (sum / free.height()).min(free.width());
+        let mut y = free.y0;
+        for area in row {
+            let y1 = (y + area / width).min(free.y1);
+            rects.push(Rect::new(free.x0, y, free.x0 + width, y1));
+            y = y1;
+        }
+
+        Rect::new(free.x0 + width, free.y0, free.x1, free.y1)
+    } else {
+        let height = (sum / free.width()).min(free.height());
+        let mut x = free.x0;
+        for area in row {
+            let x1 = (x + area / height).min(free.x1);
+            rects.push(Rect::new(x, free.y0, x1, free.y0 + height));
+            x = x1;
+        }
+
+        Rect::new(free.x0, free.y0 + height, free.x1, free.y1)
+    }
+}
+
+fn draw_label(ctx: &mut PaintCtx, env: &Env, text: String, rect: Rect) {
+    if rect.width() < LABEL_MIN_SIZE.width || rect.height() < LABEL_MIN_SIZE.height {
+        return;
+    }
+
+    let mut layout = TextLayout::<String>::from_text(text);
+    layout.set_text_color(Color::WHITE);
+    layout.set_wrap_width(rect.width() - theme::grid(1.0));
+    layout.rebuild_if_needed(ctx.text(), env);
+
+    ctx.with_save(|ctx| {
+        ctx.clip(rect);
+        layout.draw(ctx, rect.origin() + (theme::grid(0.5), theme::grid(0.5)));
+    });
+}
+
+/// Workshop items drawn as blocks of area proportional to their size, colored by state.
+///
+/// Clicking a block toggles selection of the item, selected items are outlined.
+pub struct Treemap {
+    /// Index of the item and its block.
+    blocks: Vec<(usize, Rect)>,
+}
+
+impl Treemap {
+    pub fn new() -> Self {
+        Treemap { blocks: vec![] }
+    }
+
+    fn compute_blocks(&mut self, items: &Vector<SteamWorkshopItem>, size: Size) {
+        let mut sized: Vec<(usize, f64)> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((i, item.size? as f64)))
+            .filter(|(_, size)| *size > 0.0)
+            .collect();
+        sized.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        let sizes: Vec<f64> = sized.iter().map(|(_, size)| *size).collect();
+        let rects = squarify(&sizes, size.to_rect());
+
+        self.blocks = sized.into_iter().map(|(i, _)| i).zip(rects).collect();
+    }
+}
+
+impl Widget<Vector<SteamWorkshopItem>> for Treemap {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut Vector<SteamWorkshopItem>,
+        _env: &Env,
+    ) {
+        if let Event::MouseDown(mouse) = event {
+            let clicked = self
+                .blocks
+                .iter()
+                .find(|(_, rect)| rect.contains(mouse.pos))
+                .map(|(i, _)| *i);
+
+            if let Some(item) = clicked.and_then(|i| data.get_mut(i)) {
+                item.selected = !item.selected && item.is_removable();
+                ctx.request_paint();
+            }
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        _ctx: &mut LifeCycleCtx,
+        _event: &LifeCycle,
+        _data: &Vector<SteamWorkshopItem>,
+        _env: &Env,
+    ) {
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &Vector<SteamWorkshopItem>,
+        data: &Vector<SteamWorkshopItem>,
+        _env: &Env,
+    ) {
+        if !old_data.same(data) {
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Vector<SteamWorkshopItem>,
+        _env: &Env,
+    ) -> Size {
+        let size = bc.max();
+        self.compute_blocks(data, size);
+
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Vector<SteamWorkshopItem>, env: &Env) {
+        for (i, rect) in &self.blocks {
+            let item = &data[*i];
+            let block = rect.inset(-1.0);
+
+            ctx.fill(block, &state_color(item.state));
+            if item.selected {
+                ctx.stroke(block.inset(-1.0), &env.get(theme::COLOR_ACCENT), 2.0);
+            }
+            draw_label(ctx, env, item.to_string(), block);
+        }
+    }
+}
+
+/// Apps drawn as horizontal bars of length proportional to their reclaimable space.
+///
+/// Clicking a bar selects the app.
+pub struct BarChart {
+    /// Apps with reclaimable space, largest first.
+    apps: Vec<SteamApp>,
+}
+
+impl BarChart {
+    pub fn new() -> Self {
+        BarChart { apps: vec![] }
+    }
+
+    fn update_apps(&mut self, apps: &Vector<SteamApp>) {
+        self.apps = apps
+            .iter()
+            .filter(|a| a.reclaimable_bytes > 0)
+            .cloned()
+            .collect();
+        self.apps
+            .sort_by(|a, b| b.reclaimable_bytes.cmp(&a.reclaimable_bytes));
+    }
+}
+
+impl Widget<Vector<SteamApp>> for BarChart {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        _data: &mut Vector<SteamApp>,
+        _env: &Env,
+    ) {
+        if let Event::MouseDown(mouse) = event {
+            let index = (mouse.pos.y / BAR_HEIGHT) as usize;
+
+            if let Some(app) = self.apps.get(index) {
+                ctx.submit_command(cmd::SELECT_STEAM_APP.with(app.clone()));
+            }
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        _ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &Vector<SteamApp>,
+        _env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.update_apps(data);
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &Vector<SteamApp>,
+        data: &Vector<SteamApp>,
+        _env: &Env,
+    ) {
+        if !old_data.same(data) {
+            self.update_apps(data);
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &Vector<SteamApp>,
+        _env: &Env,
+    ) -> Size {
+        bc.constrain(Size::new(
+            bc.max().width,
+            BAR_HEIGHT * self.apps.len() as f64,
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, _data: &Vector<SteamApp>, env: &Env) {
+        let max = match self.apps.first() {
+            Some(app) => app.reclaimable_bytes as f64,
+            None => return,
+        };
+        let width = ctx.size().width;
+
+        for (i, app) in self.apps.iter().enumerate() {
+            let y = BAR_HEIGHT * i as f64;
+            let bar_width = width * app.reclaimable_bytes as f64 / max;
+            let bar = Rect::from_origin_size(Point::new(0.0, y), (bar_width, BAR_HEIGHT))
+                .inset((0.0, -2.0));
+
+            ctx.fill(bar, &theme::COLOR_STATE_UNSUBSCRIBED);
+            let size = app
+                .reclaimable_bytes
+                .file_size(humansize::file_size_opts::BINARY)
+                .unwrap();
+            draw_label(
+                ctx,
+                env,
+                format!("{} - {}", app, size),
+                Rect::from_origin_size(Point::new(0.0, y), (width, BAR_HEIGHT)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+fn assert_inside(rects: &[Rect], bounds: Rect) {
+    for rect in rects {
+        assert!(
+            rect.x0.is_finite()
+                && rect.y0.is_finite()
+                && rect.x1.is_finite()
+                && rect.y1.is_finite()
+        );
+        assert!(rect.x0 >= bounds.x0 && rect.x1 <= bounds.x1, "{:?}", rect);
+        assert!(rect.y0 >= bounds.y0 && rect.y1 <= bounds.y1, "{:?}", rect);
+        assert!(rect.x0 <= rect.x1 && rect.y0 <= rect.y1, "{:?}", rect);
+    }
+}
+
+#[test]
+fn test_squarify_proportional() {
+    let bounds = Rect::new(10.0, 20.0, 610.0, 420.0);
+    let sizes = [6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0];
+    let rects = squarify(&sizes, bounds);
+
+    assert_eq!(rects.len(), sizes.len());
+    assert_inside(&rects, bounds);
+    let scale = bounds.area() / 24.0;
+    for (rect, size) in rects.iter().zip(sizes.iter()) {
+        assert!((rect.area() - size * scale).abs() < 1e-6, "{:?}", rect);
+    }
+}
+
+#[test]
+fn test_squarify_single_and_equal() {
+    let bounds = Rect::new(0.0, 0.0, 300.0, 100.0);
+    assert_eq!(squarify(&[42.0], bounds), vec![bounds]);
+
+    let square = Rect::new(0.0, 0.0, 200.0, 200.0);
+    let rects = squarify(&[1.0; 4], square);
+    assert_inside(&rects, square);
+    for rect in rects {
+        assert!((rect.width() - 100.0).abs() < 1e-9 && (rect.height() - 100.0).abs() < 1e-9);
+    }
+
+    assert_eq!(squarify(&[], bounds), vec![]);
+    assert_eq!(squarify(&[1.0], Rect::ZERO), vec![Rect::ZERO]);
+}
+
+#[test]
+fn test_squarify_tiny_items() {
+    let bounds = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let mut huge_and_tiny = vec![1e15];
+    huge_and_tiny.extend(vec![1.0; 1000]);
+    let halving: Vec<f64> = (0..200).map(|i| 1e12 * 0.5f64.powi(i)).collect();
+    let many: Vec<f64> = (1..20_000).rev().map(|i| i as f64).collect();
+
+    for sizes in &[huge_and_tiny, halving, many] {
+        let rects = squarify(sizes, bounds);
+
+        assert_eq!(rects.len(), sizes.len());
+        assert_inside(&rects, bounds);
+        let total: f64 = rects.iter().map(|r| r.area()).sum();
+        assert!((total - bounds.area()).abs() < 1e-6, "{}", total);
+    }
+}
@@ -0,0 +1,321 @@
+//! Lists of fixed height rows that only build the rows visible in the `VirtualScroll` around them.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use druid::{
+    im::Vector,
+    widget::{Scroll, Widget},
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    Point, Rect, Selector, Size, UpdateCtx, WidgetId, WidgetPod,
+};
+
+use crate::widget::keyboard::SCROLL_TO_VIEW;
+
+/// Asks a list to build the rows of its new viewport.
+pub const SYNC_ROWS: Selector = Selector::new("virtual-list.sync-rows");
+
+/// Rows built above and below the viewport, so small scrolls don't rebuild rows.
+const OVERSCAN_ROWS: usize = 5;
+/// Rows built before the list knows its viewport.
+const INITIAL_ROWS: usize = 30;
+
+pub type RowPod<T> = WidgetPod<T, Box<dyn Widget<T>>>;
+
+/// A list that builds its rows for a viewport.
+pub trait Virtualized {
+    /// Set the visible part of the list, returns id of the widget to send `SYNC_ROWS` to
+    /// if other rows are needed.
+    fn set_viewport(&mut self, viewport: Rect) -> Option<WidgetId>;
+}
+
+/// Which rows of a list with fixed row height are visible.
+pub struct RowRange {
+    row_height: f64,
+    viewport: Option<Rect>,
+    id: Option<WidgetId>,
+}
+
+impl RowRange {
+    pub fn new(row_height: f64) -> Self {
+        Self {
+            row_height,
+            viewport: None,
+            id: None,
+        }
+    }
+
+    pub fn row_height(&self) -> f64 {
+        self.row_height
+    }
+
+    /// Remember the id of the list, `SYNC_ROWS` is sent to it.
+    pub fn set_id(&mut self, id: WidgetId) {
+        self.id = Some(id);
+    }
+
+    pub fn set_viewport(&mut self, viewport: Rect) -> Option<WidgetId> {
+        let changed = self
+            .viewport
+            .map_or(true, |old| self.unclamped(old) != self.unclamped(viewport));
+        self.viewport = Some(viewport);
+
+        self.id.filter(|_| changed)
+    }
+
+    /// Rows to build for a list of `len` rows.
+    pub fn rows(&self, len: usize) -> Range<usize> {
+        let rows = match self.viewport {
+            Some(viewport) => self.unclamped(viewport),
+            None => 0..INITIAL_ROWS,
+        };
+
+        rows.start.min(len)..rows.end.min(len)
+    }
+
+    pub fn row_rect(&self, row: usize, width: f64) -> Rect {
+        Rect::from_origin_size(
+            (0.0, row as f64 * self.row_height),
+            (width, self.row_height),
+        )
+    }
+
+    /// Height of the whole list, built rows or not.
+    pub fn height(&self, len: usize) -> f64 {
+        len as f64 * self.row_height
+    }
+
+    fn unclamped(&self, viewport: Rect) -> Range<usize> {
+        let first = (viewport.y0 / self.row_height).floor().max(0.0) as usize;
+        let last = (viewport.y1 / self.row_height).ceil().max(0.0) as usize;
+
+        first.saturating_sub(OVERSCAN_ROWS)..last + OVERSCAN_ROWS
+    }
+}
+
+/// Drop rows outside of `rows` and build the missing ones, returns `true` if rows changed.
+pub fn sync_row_pods<T>(
+    pods: &mut BTreeMap<usize, RowPod<T>>,
+    rows: Range<usize>,
+    builder: impl Fn() -> Box<dyn Widget<T>>,
+) -> bool {
+    let len = pods.len();
+    pods.retain(|row, _| rows.contains(row));
+    let removed = pods.len() != len;
+
+    let mut added = false;
+    for row in rows {
+        pods.entry(row).or_insert_with(|| {
+            added = true;
+            WidgetPod::new(builder())
+        });
+    }
+
+    removed || added
+}
+
+/// Vertical `Scroll` that tells the list inside which rows are visible.
+///
+/// Also shows rects asked for by the content with `SCROLL_TO_VIEW`.
+pub struct VirtualScroll<T, W> {
+    scroll: Scroll<T, W>,
+}
+
+impl<T: Data, W: Widget<T> + Virtualized> VirtualScroll<T, W> {
+    pub fn new(child: W) -> Self {
+        Self {
+            scroll: Scroll::new(child).vertical(),
+        }
+    }
+
+    fn viewport(&self, size: Size) -> Rect {
+        Rect::from_origin_size(self.scroll.offset().to_point(), size)
+    }
+}
+
+impl<T: Data, W: Widget<T> + Virtualized> Widget<T> for VirtualScroll<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::Notification(notification) if notification.is(SCROLL_TO_VIEW) => {
+                let rect = *notification.get(SCROLL_TO_VIEW).unwrap();
+                if self.scroll.scroll_to(rect) {
+                    ctx.request_paint();
+                }
+                ctx.set_handled();
+            }
+            _ => self.scroll.event(ctx, event, data, env),
+        }
+
+        let viewport = self.viewport(ctx.size());
+        if let Some(id) = self.scroll.child_mut().set_viewport(viewport) {
+            ctx.submit_command(SYNC_ROWS.to(id));
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.scroll.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.scroll.update(ctx, old_data, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.scroll.layout(ctx, bc, data, env);
+
+        let viewport = self.viewport(size);
+        if let Some(id) = self.scroll.child_mut().set_viewport(viewport) {
+            ctx.submit_command(SYNC_ROWS.to(id));
+        }
+
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.scroll.paint(ctx, data, env)
+    }
+}
+
+/// List of rows of the same height, only rows in the viewport are built and laid out.
+pub struct VirtualList<T> {
+    row_builder: Box<dyn Fn() -> Box<dyn Widget<T>>>,
+    rows: RowRange,
+    children: BTreeMap<usize, RowPod<T>>,
+}
+
+impl<T: Data> VirtualList<T> {
+    pub fn new<W: Widget<T> + 'static>(
+        row_height: f64,
+        row_builder: impl Fn() -> W + 'static,
+    ) -> Self {
+        Self {
+            row_builder: Box::new(move || Box::new(row_builder())),
+            rows: RowRange::new(row_height),
+            children: BTreeMap::new(),
+        }
+    }
+
+    fn sync_rows(&mut self, len: usize) -> bool {
+        sync_row_pods(&mut self.children, self.rows.rows(len), &self.row_builder)
+    }
+}
+
+impl<T> Virtualized for VirtualList<T> {
+    fn set_viewport(&mut self, viewport: Rect) -> Option<WidgetId> {
+        self.rows.set_viewport(viewport)
+    }
+}
+
+impl<T: Data> Widget<Vector<T>> for VirtualList<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Vector<T>, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if cmd.is(SYNC_ROWS) {
+                if self.sync_rows(data.len()) {
+                    ctx.children_changed();
+                    ctx.request_layout();
+                }
+                return ctx.set_handled();
+            }
+        }
+
+        for (row, child) in self.children.iter_mut() {
+            if let Some(row_data) = data.get(*row) {
+                let mut new_data = row_data.clone();
+                child.event(ctx, event, &mut new_data, env);
+                if !new_data.same(row_data) {
+                    data.set(*row, new_data);
+                }
+            }
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &Vector<T>,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.rows.set_id(ctx.widget_id());
+            if self.sync_rows(data.len()) {
+                ctx.children_changed();
+            }
+        }
+
+        for (row, child) in self.children.iter_mut() {
+            if let Some(row_data) = data.get(*row) {
+                child.lifecycle(ctx, event, row_data, env);
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &Vector<T>, data: &Vector<T>, env: &Env) {
+        // like `List`, rows get the update before rows are added or removed
+        for (row, child) in self.children.iter_mut() {
+            if let Some(row_data) = data.get(*row) {
+                child.update(ctx, row_data, env);
+            }
+        }
+
+        if self.sync_rows(data.len()) {
+            ctx.children_changed();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Vector<T>,
+        env: &Env,
+    ) -> Size {
+        let width = bc.max().width;
+        let row_bc = BoxConstraints::tight(Size::new(width, self.rows.row_height()));
+
+        for (row, child) in self.children.iter_mut() {
+            if let Some(row_data) = data.get(*row) {
+                child.layout(ctx, &row_bc, row_data, env);
+                let origin = Point::new(0.0, *row as f64 * self.rows.row_height());
+                child.set_origin(ctx, row_data, env, origin);
+            }
+        }
+
+        bc.constrain(Size::new(width, self.rows.height(data.len())))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Vector<T>, env: &Env) {
+        for (row, child) in self.children.iter_mut() {
+            if let Some(row_data) = data.get(*row) {
+                child.paint(ctx, row_data, env);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_row_range() {
+    let mut rows = RowRange::new(50.0);
+    assert_eq!(rows.rows(100), 0..INITIAL_ROWS);
+    assert_eq!(rows.rows(3), 0..3);
+
+    rows.set_viewport(Rect::new(0.0, 1000.0, 200.0, 1200.0));
+    assert_eq!(rows.rows(100), 20 - OVERSCAN_ROWS..24 + OVERSCAN_ROWS);
+    assert_eq!(rows.rows(22), 20 - OVERSCAN_ROWS..22);
+}
+
+#[test]
+fn test_row_range_sync_only_on_change() {
+    let mut rows = RowRange::new(50.0);
+    let id = WidgetId::next();
+    rows.set_id(id);
+
+    assert_eq!(
+        rows.set_viewport(Rect::new(0.0, 0.0, 200.0, 400.0)),
+        Some(id)
+    );
+    assert_eq!(rows.set_viewport(Rect::new(0.0, 10.0, 200.0, 390.0)), None);
+    assert_eq!(
+        rows.set_viewport(Rect::new(0.0, 60.0, 200.0, 460.0)),
+        Some(id)
+    );
+}
@@ -0,0 +1,149 @@
+//! Steam access of the session, replaced by a fake one in tests.
+
+use std::path::{Path, PathBuf};
+
+use workshop_cleaner_core::{
+    cleaner::{ItemState, RemovalStrategy},
+    disk_usage::{DiskUsage, DiskUsageWalker},
+    helper::HelperCleaner,
+    locator::SteamLocator,
+    progress::Monitor,
+    summary::{scan_all_apps, AppSummary},
+    AppId, PublishedFileId,
+};
+use workshop_cleaner_web::{PublishedFileDetails, SteamWebClient};
+
+/// Local Steam installation and the Steam Web API.
+pub trait Backend {
+    /// Summaries of apps with workshop content, of the Steam installation at `steam_root` or
+    /// the detected one.
+    fn summarize_apps(&self, steam_root: Option<&Path>) -> Result<Vec<AppSummary>, String>;
+
+    /// Name of the app from the store, used when local Steam files don't know it.
+    fn app_name(&self, app_id: u32) -> Option<String>;
+
+    /// Workshop details of the items, unknown items are left out.
+    fn item_details(&self, ids: &[u64]) -> Result<Vec<PublishedFileDetails>, String>;
+
+    /// Initialize Steam client for the app.
+    fn connect(&self, app_id: u32) -> Result<Box<dyn AppCleaner>, String>;
+}
+
+/// Steam client of a single app, see `HelperCleaner`.
+///
+/// Long running calls report to the monitor and fail once it's cancelled.
+pub trait AppCleaner: Send {
+    fn installed_items(
+        &self,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<(PublishedFileId, ItemState)>, String>;
+
+    /// Names and sizes of directories in the workshop content directory that are not items.
+    fn unknown_folders(&self) -> Result<Vec<(String, Option<u64>)>, String>;
+
+    fn workshop_dir(&self) -> Result<PathBuf, String>;
+
+    /// Disk usage of the items in order of `item_ids`, `None` for items that could not be
+    /// measured.
+    fn items_usage(
+        &self,
+        item_ids: &[PublishedFileId],
+        walker: &DiskUsageWalker,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<Option<DiskUsage>>, String>;
+
+    fn user_name(&self, steam_id: u64) -> Option<String>;
+
+    fn remove_item(
+        &self,
+        item_id: PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+        monitor: &mut Monitor,
+    ) -> Result<(), String>;
+}
+
+/// Backend of the real Steam, each app is accessed through a helper process.
+///
+/// The executable has to call `helper::run_if_requested` at the start of `main`.
+#[derive(Clone, Default)]
+pub struct SteamBackend {
+    web: SteamWebClient,
+}
+
+impl SteamBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_web_client(mut self, web: SteamWebClient) -> Self {
+        self.web = web;
+
+        self
+    }
+}
+
+impl Backend for SteamBackend {
+    fn summarize_apps(&self, steam_root: Option<&Path>) -> Result<Vec<AppSummary>, String> {
+        let mut locator = match steam_root {
+            Some(root) => SteamLocator::at(root.to_path_buf())?,
+            None => SteamLocator::locate()?,
+        };
+
+        Ok(scan_all_apps(&mut locator))
+    }
+
+    fn app_name(&self, app_id: u32) -> Option<String> {
+        self.web.get_app_details(app_id).ok().map(|d| d.name)
+    }
+
+    fn item_details(&self, ids: &[u64]) -> Result<Vec<PublishedFileDetails>, String> {
+        self.web
+            .get_published_file_details(ids)
+            .map_err(|e| e.to_string())
+    }
+
+    fn connect(&self, app_id: u32) -> Result<Box<dyn AppCleaner>, String> {
+        Ok(Box::new(HelperCleaner::spawn(AppId(app_id))?))
+    }
+}
+
+impl AppCleaner for HelperCleaner {
+    fn installed_items(
+        &self,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<(PublishedFileId, ItemState)>, String> {
+        self.get_installed_items_monitored(monitor)
+    }
+
+    fn unknown_folders(&self) -> Result<Vec<(String, Option<u64>)>, String> {
+        self.get_unknown_folders()
+    }
+
+    fn workshop_dir(&self) -> Result<PathBuf, String> {
+        self.get_workshop_dir()
+    }
+
+    fn items_usage(
+        &self,
+        item_ids: &[PublishedFileId],
+        walker: &DiskUsageWalker,
+        monitor: &mut Monitor,
+    ) -> Result<Vec<Option<DiskUsage>>, String> {
+        self.get_items_usage(item_ids, walker, monitor)
+    }
+
+    fn user_name(&self, steam_id: u64) -> Option<String> {
+        self.get_user_name(steam_id)
+    }
+
+    fn remove_item(
+        &self,
+        item_id: PublishedFileId,
+        unsubscribe: bool,
+        strategy: &RemovalStrategy,
+        monitor: &mut Monitor,
+    ) -> Result<(), String> {
+        self.remove_item_monitored(&item_id, unsubscribe, strategy, monitor)
+    }
+}
@@ -0,0 +1,10 @@
+//! Cleaning workflow shared by the frontends: locate apps, pick one, scan its items, load their
+//! details and remove the ones chosen.
+
+pub use backend::{AppCleaner, Backend, SteamBackend};
+pub use model::{App, Item, ItemDetails, RemovalState};
+pub use session::{Canceller, Command, Event, RemovalSummary, Session, SessionHandle, State};
+
+pub mod backend;
+pub mod model;
+pub mod session;
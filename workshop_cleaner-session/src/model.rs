@@ -0,0 +1,133 @@
+use std::fmt;
+
+use workshop_cleaner_core::{cleaner::ItemState, summary::AppSummary};
+
+/// App with workshop content, as found in the Steam libraries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct App {
+    pub app_id: u32,
+    pub name: String,
+    pub installed_items: usize,
+    /// Installed items no account on this machine is subscribed to.
+    pub orphaned_items: usize,
+    pub reclaimable_bytes: u64,
+}
+
+impl App {
+    pub(crate) fn new(summary: &AppSummary, name: String) -> Self {
+        App {
+            app_id: summary.app.app_id.0,
+            name,
+            installed_items: summary.installed_items,
+            orphaned_items: summary.orphaned_items.len(),
+            reclaimable_bytes: summary.reclaimable_bytes,
+        }
+    }
+}
+
+impl fmt::Display for App {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.app_id)
+    }
+}
+
+/// Installed workshop item of the selected app, or a folder that is not one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Item {
+    /// Workshop item ID, `0` for unknown folders.
+    pub id: u64,
+    /// Name of a folder that is not a workshop item.
+    pub folder: Option<String>,
+    pub state: ItemState,
+    /// Details below are loaded after the scan, `None` until they arrive or if unknown.
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub preview_url: Option<String>,
+    /// Bytes size on disk.
+    pub size: Option<u64>,
+    /// Unix timestamp (seconds) of the last update in the workshop.
+    pub time_updated: Option<u64>,
+    pub removal: RemovalState,
+}
+
+impl Item {
+    pub fn new(id: u64, state: ItemState) -> Self {
+        Item {
+            id,
+            folder: None,
+            state,
+            title: None,
+            author: None,
+            description: None,
+            preview_url: None,
+            size: None,
+            time_updated: None,
+            removal: RemovalState::Installed,
+        }
+    }
+
+    pub fn unknown_folder(name: String, size: Option<u64>) -> Self {
+        Item {
+            folder: Some(name),
+            size,
+            ..Self::new(0, ItemState::UnknownFolder)
+        }
+    }
+
+    /// Name of the item folder in the workshop content directory.
+    pub fn dir_name(&self) -> String {
+        match &self.folder {
+            Some(folder) => folder.clone(),
+            None => self.id.to_string(),
+        }
+    }
+
+    /// Item is not used by Steam, still on the disk and no removal is in progress.
+    pub fn is_removable(&self) -> bool {
+        self.state == ItemState::Unsubscribed
+            && matches!(
+                self.removal,
+                RemovalState::Installed | RemovalState::Failed(_)
+            )
+    }
+
+    pub fn set_details(&mut self, details: &ItemDetails) {
+        self.title = Some(details.title.clone());
+        self.author = details.author.clone();
+        self.description = Some(details.description.clone()).filter(|d| !d.is_empty());
+        self.preview_url = Some(details.preview_url.clone()).filter(|u| !u.is_empty());
+        self.time_updated = Some(details.time_updated);
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.folder, &self.title) {
+            (Some(folder), _) => write!(f, "{}", folder),
+            (None, Some(title)) => write!(f, "{} ({})", title, self.id),
+            (None, None) => write!(f, "Unknown ({})", self.id),
+        }
+    }
+}
+
+/// Workshop details of an item, loaded from the Steam Web API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemDetails {
+    pub title: String,
+    pub author: Option<String>,
+    pub description: String,
+    pub preview_url: String,
+    pub time_updated: u64,
+}
+
+/// Progress of the workshop item removal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemovalState {
+    Installed,
+    /// Waiting for other items of the same removal job.
+    Queued,
+    Removing,
+    Removed,
+    Failed(String),
+}
@@ -0,0 +1,948 @@
+//! State machine of the cleaning workflow, frontends send it commands and show its events.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Sender},
+        Arc, Mutex,
+    },
+};
+
+use workshop_cleaner_core::{
+    cleaner::{ItemState, RemovalStrategy},
+    disk_usage::DiskUsageWalker,
+    progress::{CancelToken, Monitor, Progress},
+    PublishedFileId,
+};
+
+use crate::{
+    backend::{AppCleaner, Backend},
+    model::{App, Item, ItemDetails, RemovalState},
+};
+
+/// Maximum number of items in a single workshop details request.
+const DETAILS_CHUNK_SIZE: usize = 100;
+
+/// Number of items measured in one pass, sizes are emitted once the pass ends.
+const SIZES_CHUNK_SIZE: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Find apps with workshop content, in the Steam installation at `steam_root` or the
+    /// detected one.
+    LocateApps { steam_root: Option<PathBuf> },
+    /// Connect to Steam as the app, scan its items and load their details and sizes.
+    SelectApp(u32),
+    /// Remove items of the selected app one by one, stops before the next item once cancelled.
+    Remove {
+        ids: Vec<u64>,
+        unsubscribe: bool,
+        strategy: RemovalStrategy,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    /// Apps were not located yet, or locating them failed.
+    Start,
+    LocatingApps,
+    /// Apps are known, none is selected.
+    AppsLocated,
+    Scanning,
+    /// Items of the selected app are known, their details and sizes are being loaded.
+    LoadingDetails,
+    Ready,
+    Removing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    StateChanged(State),
+    AppsLocated(Vec<App>),
+    AppsFailed(String),
+    /// Scan events carry app ID, so events of a previously selected app can be ignored.
+    ScanProgress {
+        app_id: u32,
+        text: String,
+    },
+    ScanFailed {
+        app_id: u32,
+        error: String,
+    },
+    ScanFinished {
+        app_id: u32,
+        items: Vec<Item>,
+        workshop_dir: Option<PathBuf>,
+    },
+    ItemDetails(u64, ItemDetails),
+    ItemSize(u64, Option<u64>),
+    /// Some details could not be loaded, the items are shown without them.
+    DetailsFailed(String),
+    /// Some items could not be measured, their size is unknown.
+    SizesFailed(String),
+    /// Removal state of the item changed.
+    ItemRemoval(Item),
+    /// Removal did not start, no item was touched.
+    RemovalFailed(String),
+    RemovalFinished(RemovalSummary),
+    /// Progress of the running scan, measurement or removal, reported by core.
+    Progress(Progress),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RemovalSummary {
+    pub removed: usize,
+    pub failed: usize,
+    pub total: usize,
+}
+
+impl RemovalSummary {
+    /// Removal was cancelled before it got to all items.
+    pub fn is_cancelled(&self) -> bool {
+        self.removed + self.failed < self.total
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    sent: AtomicU64,
+    /// Commands numbered below this are cancelled.
+    cancelled_below: AtomicU64,
+    /// Cancels core operations of the running command.
+    running: Mutex<CancelToken>,
+}
+
+/// Cancels the running command of a session and the commands sent before it.
+///
+/// The running command stops between files of the item it works on. Commands sent afterwards
+/// run normally.
+#[derive(Clone, Default)]
+pub struct Canceller {
+    counters: Arc<Counters>,
+}
+
+impl Canceller {
+    pub fn cancel(&self) {
+        let sent = self.counters.sent.load(Ordering::SeqCst);
+
+        self.counters.cancelled_below.store(sent, Ordering::SeqCst);
+        if let Ok(running) = self.counters.running.lock() {
+            running.cancel();
+        }
+    }
+
+    fn next(&self) -> u64 {
+        self.counters.sent.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn is_cancelled(&self, command: u64) -> bool {
+        command < self.counters.cancelled_below.load(Ordering::SeqCst)
+    }
+
+    /// Cancel token of core operations of the command, that starts to run now.
+    fn start(&self, command: u64) -> CancelToken {
+        let token = CancelToken::new();
+        if let Ok(mut running) = self.counters.running.lock() {
+            *running = token.clone();
+        }
+
+        // cancelled while it was queued
+        if self.is_cancelled(command) {
+            token.cancel();
+        }
+
+        token
+    }
+}
+
+type Observer = dyn FnMut(Event) + Send;
+
+/// Apps, the selected app and its items, changed only by commands.
+///
+/// Every change is reported to the observer, so a frontend can follow the session without
+/// reading it back.
+pub struct Session<B> {
+    backend: B,
+    observer: Box<Observer>,
+    canceller: Canceller,
+    /// Number of the running command.
+    command: u64,
+    /// Cancel token of the running command, passed to core operations.
+    token: CancelToken,
+    state: State,
+    apps: Vec<App>,
+    app_id: Option<u32>,
+    cleaner: Option<Box<dyn AppCleaner>>,
+    workshop_dir: Option<PathBuf>,
+    items: Vec<Item>,
+    walker: DiskUsageWalker,
+}
+
+impl<B: Backend> Session<B> {
+    pub fn new(backend: B, observer: impl FnMut(Event) + Send + 'static) -> Self {
+        Session {
+            backend,
+            observer: Box::new(observer),
+            canceller: Canceller::default(),
+            command: 0,
+            token: CancelToken::new(),
+            state: State::Start,
+            apps: vec![],
+            app_id: None,
+            cleaner: None,
+            workshop_dir: None,
+            items: vec![],
+            walker: DiskUsageWalker::new(),
+        }
+    }
+
+    /// Walker measuring the items, its mode decides which size the items get.
+    pub fn with_disk_usage_walker(mut self, walker: DiskUsageWalker) -> Self {
+        self.walker = walker;
+
+        self
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn apps(&self) -> &[App] {
+        &self.apps
+    }
+
+    /// ID of the selected app, `None` until its scan succeeds.
+    pub fn app_id(&self) -> Option<u32> {
+        self.app_id
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Workshop content directory of the selected app, items are its subdirectories.
+    pub fn workshop_dir(&self) -> Option<&PathBuf> {
+        self.workshop_dir.as_ref()
+    }
+
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+
+    /// Run the command to the end, errors are reported as events too.
+    pub fn handle(&mut self, command: Command) -> Result<(), String> {
+        let number = self.canceller.next();
+
+        self.run(number, command)
+    }
+
+    /// Run commands on a background thread, in the order they are sent.
+    pub fn spawn(self) -> SessionHandle
+    where
+        B: Send + 'static,
+    {
+        let (sender, receiver) = channel::<(u64, Command)>();
+        let canceller = self.canceller.clone();
+
+        let mut session = self;
+        std::thread::spawn(move || {
+            for (number, command) in receiver {
+                let _ = session.run(number, command);
+            }
+        });
+
+        SessionHandle { sender, canceller }
+    }
+
+    fn run(&mut self, number: u64, command: Command) -> Result<(), String> {
+        self.command = number;
+        self.token = self.canceller.start(number);
+
+        match command {
+            Command::LocateApps { steam_root } => self.locate_apps(steam_root),
+            Command::SelectApp(app_id) => self.select_app(app_id),
+            Command::Remove {
+                ids,
+                unsubscribe,
+                strategy,
+            } => self.remove(ids, unsubscribe, strategy),
+        }
+    }
+
+    fn locate_apps(&mut self, steam_root: Option<PathBuf>) -> Result<(), String> {
+        self.set_state(State::LocatingApps);
+        self.close_app();
+
+        let summaries = match self.backend.summarize_apps(steam_root.as_deref()) {
+            Ok(summaries) => summaries,
+            Err(e) => {
+                self.apps.clear();
+                self.emit(Event::AppsFailed(e.clone()));
+                self.set_state(State::Start);
+
+                return Err(e);
+            }
+        };
+
+        self.apps = summaries
+            .iter()
+            .map(|s| {
+                let name = s
+                    .app
+                    .name
+                    .clone()
+                    .or_else(|| self.backend.app_name(s.app.app_id.0))
+                    .unwrap_or_else(|| "Unknown name".to_string());
+
+                App::new(s, name)
+            })
+            .collect();
+        self.emit(Event::AppsLocated(self.apps.clone()));
+        self.set_state(State::AppsLocated);
+
+        Ok(())
+    }
+
+    fn select_app(&mut self, app_id: u32) -> Result<(), String> {
+        // Steam allows one app per process, helper of the previous app is shut down first
+        self.close_app();
+        self.set_state(State::Scanning);
+
+        let name = match self.apps.iter().find(|a| a.app_id == app_id) {
+            Some(app) => app.to_string(),
+            None => app_id.to_string(),
+        };
+        self.scan_progress(app_id, format!("Connecting to Steam as {}...", name));
+        let cleaner = match self.backend.connect(app_id) {
+            Ok(cleaner) => cleaner,
+            Err(e) => {
+                return self.scan_failed(app_id, format!("Could not connect to Steam: {}", e))
+            }
+        };
+
+        self.scan_progress(
+            app_id,
+            "Looking for installed workshop items...".to_string(),
+        );
+        let observer = &mut self.observer;
+        let mut monitor = Monitor::new(self.token.clone(), |p| observer(Event::Progress(p)));
+        let found = cleaner.installed_items(&mut monitor);
+        drop(monitor);
+        let mut found = match found {
+            Ok(items) => items,
+            Err(e) => {
+                return self.scan_failed(app_id, format!("Could not read workshop items: {}", e))
+            }
+        };
+        // items that can be removed first
+        found.sort_by_key(|(_, state)| *state != ItemState::Unsubscribed);
+        let unknown_folders = cleaner.unknown_folders().unwrap_or_default();
+
+        self.app_id = Some(app_id);
+        self.workshop_dir = cleaner.workshop_dir().ok();
+        self.items = found
+            .into_iter()
+            .map(|(id, state)| Item::new(id.0, state))
+            .chain(
+                unknown_folders
+                    .into_iter()
+                    .map(|(name, size)| Item::unknown_folder(name, size)),
+            )
+            .collect();
+        self.cleaner = Some(cleaner);
+        self.emit(Event::ScanFinished {
+            app_id,
+            items: self.items.clone(),
+            workshop_dir: self.workshop_dir.clone(),
+        });
+
+        self.set_state(State::LoadingDetails);
+        self.load_details();
+        self.load_sizes();
+        self.set_state(State::Ready);
+
+        Ok(())
+    }
+
+    fn scan_progress(&mut self, app_id: u32, text: String) {
+        self.emit(Event::ScanProgress { app_id, text });
+    }
+
+    fn scan_failed(&mut self, app_id: u32, error: String) -> Result<(), String> {
+        self.emit(Event::ScanFailed {
+            app_id,
+            error: error.clone(),
+        });
+        self.set_state(State::AppsLocated);
+
+        Err(error)
+    }
+
+    /// Load workshop details of the items, each one is reported as soon as it is known.
+    fn load_details(&mut self) {
+        let ids: Vec<u64> = self.workshop_items().map(|i| i.id).collect();
+
+        for chunk in ids.chunks(DETAILS_CHUNK_SIZE) {
+            if self.is_cancelled() {
+                return;
+            }
+
+            let details = match self.backend.item_details(chunk) {
+                Ok(details) => details,
+                Err(e) => {
+                    self.emit(Event::DetailsFailed(format!(
+                        "Could not load workshop details: {}",
+                        e
+                    )));
+                    continue;
+                }
+            };
+
+            for d in details {
+                let details = ItemDetails {
+                    author: self.cleaner.as_ref().and_then(|c| c.user_name(d.creator)),
+                    title: d.title,
+                    description: d.description,
+                    preview_url: d.preview_url,
+                    time_updated: d.time_updated,
+                };
+
+                if let Some(item) = self.item_mut(d.publishedfileid) {
+                    item.set_details(&details);
+                }
+                self.emit(Event::ItemDetails(d.publishedfileid, details));
+            }
+        }
+    }
+
+    fn load_sizes(&mut self) {
+        let ids: Vec<PublishedFileId> = self
+            .workshop_items()
+            .map(|i| PublishedFileId(i.id))
+            .collect();
+
+        for chunk in ids.chunks(SIZES_CHUNK_SIZE) {
+            if self.is_cancelled() {
+                return;
+            }
+
+            let observer = &mut self.observer;
+            let mut monitor = Monitor::new(self.token.clone(), |p| observer(Event::Progress(p)));
+            let usage = match &self.cleaner {
+                Some(cleaner) => cleaner.items_usage(chunk, &self.walker, &mut monitor),
+                None => return,
+            };
+            drop(monitor);
+            // cancelled while the items were walked
+            let usage = match usage {
+                Ok(usage) => usage,
+                Err(_) if self.is_cancelled() => return,
+                Err(e) => {
+                    self.emit(Event::SizesFailed(format!(
+                        "Could not measure items: {}",
+                        e
+                    )));
+                    vec![None; chunk.len()]
+                }
+            };
+
+            for (id, usage) in chunk.iter().zip(usage) {
+                let size = usage.map(|u| u.bytes(self.walker.mode()));
+                if let Some(item) = self.item_mut(id.0) {
+                    item.size = size;
+                }
+                self.emit(Event::ItemSize(id.0, size));
+            }
+        }
+    }
+
+    fn remove(
+        &mut self,
+        ids: Vec<u64>,
+        unsubscribe: bool,
+        strategy: RemovalStrategy,
+    ) -> Result<(), String> {
+        if let Err(e) = self.check_removal(&ids) {
+            self.emit(Event::RemovalFailed(e.clone()));
+
+            return Err(e);
+        }
+
+        let previous_state = self.state;
+        self.set_state(State::Removing);
+        for id in &ids {
+            self.set_removal(*id, RemovalState::Queued);
+        }
+
+        let mut summary = RemovalSummary {
+            total: ids.len(),
+            ..RemovalSummary::default()
+        };
+        for id in &ids {
+            if self.is_cancelled() {
+                break;
+            }
+
+            self.set_removal(*id, RemovalState::Removing);
+            let observer = &mut self.observer;
+            let mut monitor = Monitor::new(self.token.clone(), |p| observer(Event::Progress(p)));
+            let result = match &self.cleaner {
+                Some(cleaner) => {
+                    cleaner.remove_item(PublishedFileId(*id), unsubscribe, &strategy, &mut monitor)
+                }
+                None => Err("Steam client of the app was closed".to_string()),
+            };
+            drop(monitor);
+            match result {
+                Ok(_) => {
+                    summary.removed += 1;
+                    self.set_removal(*id, RemovalState::Removed);
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    self.set_removal(*id, RemovalState::Failed(e));
+                }
+            }
+        }
+
+        // items the removal did not get to are left installed
+        for id in &ids {
+            if self.item_mut(*id).map(|i| &i.removal) == Some(&RemovalState::Queued) {
+                self.set_removal(*id, RemovalState::Installed);
+            }
+        }
+
+        self.emit(Event::RemovalFinished(summary));
+        self.set_state(previous_state);
+
+        Ok(())
+    }
+
+    fn check_removal(&self, ids: &[u64]) -> Result<(), String> {
+        if self.cleaner.is_none() {
+            return Err("No app is selected".to_string());
+        }
+
+        for id in ids {
+            match self
+                .items
+                .iter()
+                .find(|i| i.id == *id && i.folder.is_none())
+            {
+                Some(item) if item.is_removable() => (),
+                Some(item) => return Err(format!("Item {} can not be removed", item)),
+                None => return Err(format!("Item {} is not installed", id)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_removal(&mut self, id: u64, removal: RemovalState) {
+        if let Some(item) = self.item_mut(id) {
+            item.removal = removal;
+            let item = item.clone();
+
+            self.emit(Event::ItemRemoval(item));
+        }
+    }
+
+    /// Shut down Steam client of the selected app and forget its items.
+    fn close_app(&mut self) {
+        self.cleaner = None;
+        self.app_id = None;
+        self.workshop_dir = None;
+        self.items.clear();
+    }
+
+    fn workshop_items(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter().filter(|i| i.folder.is_none())
+    }
+
+    fn item_mut(&mut self, id: u64) -> Option<&mut Item> {
+        self.items
+            .iter_mut()
+            .find(|i| i.id == id && i.folder.is_none())
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.canceller.is_cancelled(self.command)
+    }
+
+    fn set_state(&mut self, state: State) {
+        if self.state != state {
+            self.state = state;
+            self.emit(Event::StateChanged(state));
+        }
+    }
+
+    fn emit(&mut self, event: Event) {
+        (self.observer)(event)
+    }
+}
+
+/// Session running on a background thread, see `Session::spawn`.
+pub struct SessionHandle {
+    sender: Sender<(u64, Command)>,
+    canceller: Canceller,
+}
+
+impl SessionHandle {
+    /// Queue the command, it runs once the commands sent before are done.
+    pub fn send(&self, command: Command) {
+        let number = self.canceller.next();
+
+        let _ = self.sender.send((number, command));
+    }
+
+    /// Cancel the running command and the queued ones.
+    pub fn cancel(&self) {
+        self.canceller.cancel()
+    }
+}
+
+#[cfg(test)]
+mod fake {
+    use std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+
+    use workshop_cleaner_core::{
+        cleaner::{ItemState, RemovalStrategy},
+        disk_usage::{DiskUsage, DiskUsageWalker},
+        locator::WorkshopApp,
+        progress::{Monitor, Progress},
+        summary::AppSummary,
+        AppId, PublishedFileId,
+    };
+    use workshop_cleaner_web::PublishedFileDetails;
+
+    use super::Canceller;
+    use crate::backend::{AppCleaner, Backend};
+
+    /// Steam with one app (`107410`) and its items.
+    #[derive(Clone, Default)]
+    pub struct FakeSteam {
+        pub items: Vec<(u64, ItemState)>,
+        pub titles: HashMap<u64, String>,
+        /// Items that fail to be measured or removed, with the reason.
+        pub broken: HashMap<u64, String>,
+        pub removed: Arc<Mutex<Vec<u64>>>,
+        /// Cancelled once the first item is removed.
+        pub cancel_on_remove: Option<Canceller>,
+    }
+
+    impl FakeSteam {
+        pub fn with_items(items: Vec<(u64, ItemState)>) -> Self {
+            FakeSteam {
+                items,
+                ..Self::default()
+            }
+        }
+    }
+
+    impl Backend for FakeSteam {
+        fn summarize_apps(&self, steam_root: Option<&Path>) -> Result<Vec<AppSummary>, String> {
+            if steam_root.is_some() {
+                return Err("Not a Steam installation".to_string());
+            }
+
+            Ok(vec![AppSummary {
+                app: WorkshopApp {
+                    app_id: AppId(107410),
+                    name: None,
+                    library: PathBuf::from("steamapps"),
+                },
+                installed_items: self.items.len(),
+                orphaned_items: vec![],
+                reclaimable_bytes: 0,
+            }])
+        }
+
+        fn app_name(&self, _app_id: u32) -> Option<String> {
+            Some("Arma 3".to_string())
+        }
+
+        fn item_details(&self, ids: &[u64]) -> Result<Vec<PublishedFileDetails>, String> {
+            Ok(ids
+                .iter()
+                .filter_map(|id| {
+                    Some(PublishedFileDetails {
+                        publishedfileid: *id,
+                        result: 1,
+                        title: self.titles.get(id)?.clone(),
+                        description: String::new(),
+                        creator: 76561197960287930,
+                        consumer_app_id: 107410,
+                        file_size: 0,
+                        preview_url: String::new(),
+                        time_created: 0,
+                        time_updated: 1600000000,
+                        tags: vec![],
+                    })
+                })
+                .collect())
+        }
+
+        fn connect(&self, app_id: u32) -> Result<Box<dyn AppCleaner>, String> {
+            match app_id {
+                107410 => Ok(Box::new(self.clone())),
+                _ => Err(format!("App {} is not installed", app_id)),
+            }
+        }
+    }
+
+    impl AppCleaner for FakeSteam {
+        fn installed_items(
+            &self,
+            _monitor: &mut Monitor,
+        ) -> Result<Vec<(PublishedFileId, ItemState)>, String> {
+            Ok(self
+                .items
+                .iter()
+                .map(|(id, state)| (PublishedFileId(*id), *state))
+                .collect())
+        }
+
+        fn unknown_folders(&self) -> Result<Vec<(String, Option<u64>)>, String> {
+            Ok(vec![("backup".to_string(), Some(10))])
+        }
+
+        fn workshop_dir(&self) -> Result<PathBuf, String> {
+            Ok(PathBuf::from("steamapps/workshop/content/107410"))
+        }
+
+        fn items_usage(
+            &self,
+            item_ids: &[PublishedFileId],
+            walker: &DiskUsageWalker,
+            monitor: &mut Monitor,
+        ) -> Result<Vec<Option<DiskUsage>>, String> {
+            if let Some(reason) = item_ids.iter().find_map(|id| self.broken.get(&id.0)) {
+                return Err(reason.clone());
+            }
+
+            Ok(item_ids
+                .iter()
+                .map(|id| {
+                    let usage = DiskUsage {
+                        apparent_bytes: id.0 * 1000,
+                        allocated_bytes: id.0 * 1024,
+                        files: 1,
+                        largest_files: vec![],
+                    };
+                    monitor.report(Progress::ItemMeasured {
+                        id: id.0,
+                        bytes: usage.bytes(walker.mode()),
+                        files: 1,
+                    });
+
+                    Some(usage)
+                })
+                .collect())
+        }
+
+        fn user_name(&self, _steam_id: u64) -> Option<String> {
+            Some("Rabbit".to_string())
+        }
+
+        fn remove_item(
+            &self,
+            item_id: PublishedFileId,
+            _unsubscribe: bool,
+            _strategy: &RemovalStrategy,
+            monitor: &mut Monitor,
+        ) -> Result<(), String> {
+            if let Some(reason) = self.broken.get(&item_id.0) {
+                return Err(reason.clone());
+            }
+
+            self.removed.lock().unwrap().push(item_id.0);
+            if let Some(canceller) = &self.cancel_on_remove {
+                canceller.cancel();
+                // core operation of the item stops too
+                assert!(monitor.token().is_cancelled());
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_session(
+    steam: fake::FakeSteam,
+) -> (Session<fake::FakeSteam>, Arc<std::sync::Mutex<Vec<Event>>>) {
+    let events = Arc::new(std::sync::Mutex::new(vec![]));
+    let log = events.clone();
+    let session = Session::new(steam, move |e| log.lock().unwrap().push(e));
+
+    (session, events)
+}
+
+#[cfg(test)]
+fn remove_command(ids: Vec<u64>) -> Command {
+    Command::Remove {
+        ids,
+        unsubscribe: true,
+        strategy: RemovalStrategy::Delete,
+    }
+}
+
+#[test]
+fn test_session_workflow() {
+    let mut steam =
+        fake::FakeSteam::with_items(vec![(2, ItemState::Pinned), (1, ItemState::Unsubscribed)]);
+    steam.titles.insert(1, "Altis Life".to_string());
+    let removed = steam.removed.clone();
+    let (mut session, events) = test_session(steam);
+
+    session
+        .handle(Command::LocateApps { steam_root: None })
+        .unwrap();
+    assert_eq!(session.state(), State::AppsLocated);
+    assert_eq!(session.apps()[0].to_string(), "Arma 3 (107410)");
+
+    session.handle(Command::SelectApp(107410)).unwrap();
+    assert_eq!(session.state(), State::Ready);
+    assert_eq!(session.app_id(), Some(107410));
+    let items: Vec<String> = session.items().iter().map(|i| i.to_string()).collect();
+    assert_eq!(items, ["Altis Life (1)", "Unknown (2)", "backup"]);
+    assert_eq!(session.items()[0].author.as_deref(), Some("Rabbit"));
+    assert_eq!(session.items()[1].size, Some(2000));
+    assert_eq!(session.items()[2].size, Some(10));
+    assert!(events
+        .lock()
+        .unwrap()
+        .contains(&Event::Progress(Progress::ItemMeasured {
+            id: 2,
+            bytes: 2000,
+            files: 1
+        })));
+
+    session.handle(remove_command(vec![1])).unwrap();
+    assert_eq!(*removed.lock().unwrap(), [1]);
+    assert_eq!(session.items()[0].removal, RemovalState::Removed);
+    assert_eq!(session.state(), State::Ready);
+
+    let events = events.lock().unwrap();
+    let removal: Vec<&RemovalState> = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::ItemRemoval(item) => Some(&item.removal),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        removal,
+        [
+            &RemovalState::Queued,
+            &RemovalState::Removing,
+            &RemovalState::Removed
+        ]
+    );
+    let summary = RemovalSummary {
+        removed: 1,
+        failed: 0,
+        total: 1,
+    };
+    assert_eq!(
+        events[events.len() - 2..],
+        [
+            Event::RemovalFinished(summary),
+            Event::StateChanged(State::Ready)
+        ]
+    );
+}
+
+#[test]
+fn test_session_allocated_sizes() {
+    let steam = fake::FakeSteam::with_items(vec![(1, ItemState::Unsubscribed)]);
+    let (session, _) = test_session(steam);
+    let mut session = session.with_disk_usage_walker(
+        DiskUsageWalker::new().with_mode(workshop_cleaner_core::disk_usage::SizeMode::Allocated),
+    );
+
+    session.handle(Command::SelectApp(107410)).unwrap();
+    assert_eq!(session.items()[0].size, Some(1024));
+}
+
+#[test]
+fn test_session_failures() {
+    let mut steam =
+        fake::FakeSteam::with_items(vec![(1, ItemState::Unsubscribed), (2, ItemState::Pinned)]);
+    steam.broken.insert(1, "Access denied".to_string());
+    let (mut session, events) = test_session(steam);
+
+    let root = Some(PathBuf::from("nowhere"));
+    assert!(session
+        .handle(Command::LocateApps { steam_root: root })
+        .is_err());
+    assert_eq!(session.state(), State::Start);
+
+    session
+        .handle(Command::LocateApps { steam_root: None })
+        .unwrap();
+    assert!(session.handle(Command::SelectApp(4000)).is_err());
+    assert_eq!(session.state(), State::AppsLocated);
+    assert_eq!(
+        session.handle(remove_command(vec![1])),
+        Err("No app is selected".to_string())
+    );
+
+    session.handle(Command::SelectApp(107410)).unwrap();
+    assert_eq!(
+        session.handle(remove_command(vec![2])),
+        Err("Item Unknown (2) can not be removed".to_string())
+    );
+
+    session.handle(remove_command(vec![1])).unwrap();
+    assert_eq!(
+        session.items()[0].removal,
+        RemovalState::Failed("Access denied".to_string())
+    );
+    assert!(session.items()[0].is_removable());
+    assert_eq!(session.items()[0].size, None);
+    assert!(events.lock().unwrap().contains(&Event::SizesFailed(
+        "Could not measure items: Access denied".to_string()
+    )));
+    assert!(events.lock().unwrap().contains(&Event::ScanFailed {
+        app_id: 4000,
+        error: "Could not connect to Steam: App 4000 is not installed".to_string()
+    }));
+}
+
+#[test]
+fn test_session_cancel_removal() {
+    let mut steam = fake::FakeSteam::with_items(vec![
+        (1, ItemState::Unsubscribed),
+        (2, ItemState::Unsubscribed),
+    ]);
+    let removed = steam.removed.clone();
+    let (mut session, events) = test_session(steam.clone());
+    steam.cancel_on_remove = Some(session.canceller());
+    session.backend = steam;
+
+    session.handle(Command::SelectApp(107410)).unwrap();
+    session.handle(remove_command(vec![1, 2])).unwrap();
+
+    assert_eq!(*removed.lock().unwrap(), [1]);
+    assert_eq!(session.items()[1].removal, RemovalState::Installed);
+    let summary = RemovalSummary {
+        removed: 1,
+        failed: 0,
+        total: 2,
+    };
+    assert!(summary.is_cancelled());
+    assert!(events
+        .lock()
+        .unwrap()
+        .contains(&Event::RemovalFinished(summary)));
+
+    // commands sent after the cancellation run normally
+    session.handle(remove_command(vec![2])).unwrap();
+    assert_eq!(*removed.lock().unwrap(), [1, 2]);
+}
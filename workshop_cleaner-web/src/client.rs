@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+use reqwest::blocking::{Client, RequestBuilder, Response};
+
+use crate::model::{
+    AppDetails, AppDetailsEntry, PublishedFileDetails, PublishedFileDetailsResponse,
+};
+use crate::{Error, Result};
+
+pub const STORE_URL: &str = "https://store.steampowered.com";
+pub const API_URL: &str = "https://api.steampowered.com";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Blocking client for the parts of Steam store and Web API used by the cleaner.
+///
+/// Cloning is cheap, clones share the same connection pool.
+#[derive(Clone, Debug)]
+pub struct SteamWebClient {
+    http: Client,
+    store_url: String,
+    api_url: String,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl SteamWebClient {
+    pub fn new() -> Self {
+        SteamWebClient {
+            http: Self::build_http(DEFAULT_TIMEOUT),
+            store_url: STORE_URL.to_string(),
+            api_url: API_URL.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+        }
+    }
+
+    /// Builder style method for setting the store base URL (`appdetails` endpoint).
+    pub fn with_store_url(mut self, url: impl Into<String>) -> Self {
+        self.store_url = url.into().trim_end_matches('/').to_string();
+
+        self
+    }
+
+    /// Builder style method for setting the Web API base URL (`ISteamRemoteStorage` endpoints).
+    pub fn with_api_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = url.into().trim_end_matches('/').to_string();
+
+        self
+    }
+
+    /// Builder style method for setting the timeout of a single request attempt.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = Self::build_http(timeout);
+
+        self
+    }
+
+    /// Builder style method for setting how many times failed requests are retried.
+    ///
+    /// Only transient failures (timeouts, connection errors, `429` and `5xx` responses) are retried.
+    /// Wait time before each retry is doubled, starting at `backoff`.
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+
+        self
+    }
+
+    /// Get store details of the app.
+    pub fn get_app_details(&self, app_id: u32) -> Result<AppDetails> {
+        let url = format!("{}/api/appdetails", self.store_url);
+        let response = self.send(|| self.http.get(&url).query(&[("appids", app_id)]))?;
+
+        let mut entries: HashMap<String, AppDetailsEntry> =
+            serde_json::from_str(&response.text()?)?;
+
+        match entries.remove(&app_id.to_string()) {
+            Some(AppDetailsEntry {
+                success: true,
+                data: Some(details),
+            }) => Ok(details),
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    /// Get details of multiple workshop items in one request.
+    ///
+    /// Items unknown to Steam (deleted, private) are left out of the result.
+    pub fn get_published_file_details(
+        &self,
+        item_ids: &[u64],
+    ) -> Result<Vec<PublishedFileDetails>> {
+        if item_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let url = format!(
+            "{}/ISteamRemoteStorage/GetPublishedFileDetails/v1/",
+            self.api_url
+        );
+        let mut form = vec![("itemcount".to_string(), item_ids.len().to_string())];
+        for (i, id) in item_ids.iter().enumerate() {
+            form.push((format!("publishedfileids[{}]", i), id.to_string()));
+        }
+
+        let response = self.send(|| self.http.post(&url).form(&form))?;
+        let details: PublishedFileDetailsResponse = serde_json::from_str(&response.text()?)?;
+
+        Ok(details
+            .response
+            .publishedfiledetails
+            .into_iter()
+            .filter(|d| d.result == 1)
+            .collect())
+    }
+
+    /// Get details of a single workshop item.
+    pub fn get_published_file(&self, item_id: u64) -> Result<PublishedFileDetails> {
+        self.get_published_file_details(&[item_id])?
+            .pop()
+            .ok_or(Error::NotFound)
+    }
+
+    /// Download a file, like the preview image of a workshop item.
+    pub fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.send(|| self.http.get(url))?;
+
+        Ok(response.bytes()?.to_vec())
+    }
+
+    fn build_http(timeout: Duration) -> Client {
+        Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Could not build HTTP client")
+    }
+
+    /// Send request built by `request`, retrying transient failures with exponential backoff.
+    fn send(&self, request: impl Fn() -> RequestBuilder) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let error = match request().send() {
+                Ok(r) if r.status().is_success() => return Ok(r),
+                Ok(r) => Error::Status(r.status()),
+                Err(e) => Error::Http(e),
+            };
+
+            if attempt >= self.max_retries || !error.is_transient() {
+                return Err(error);
+            }
+
+            thread::sleep(self.retry_backoff * 2u32.pow(attempt));
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for SteamWebClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{mock, Matcher};
+
+    use super::*;
+
+    fn client() -> SteamWebClient {
+        SteamWebClient::new()
+            .with_store_url(mockito::server_url())
+            .with_api_url(mockito::server_url())
+            .with_retries(2, Duration::from_millis(1))
+    }
+
+    #[test]
+    fn app_details() {
+        let _m = mock("GET", "/api/appdetails")
+            .match_query(Matcher::UrlEncoded("appids".into(), "107410".into()))
+            .with_body(
+                r#"{"107410":{"success":true,"data":{"type":"game","name":"Arma 3","steam_appid":107410,"short_description":"Experience true combat gameplay."}}}"#,
+            )
+            .create();
+
+        let details = client().get_app_details(107410).unwrap();
+
+        assert_eq!(details.name, "Arma 3");
+        assert_eq!(details.steam_appid, 107410);
+    }
+
+    #[test]
+    fn app_details_not_found() {
+        let _m = mock("GET", "/api/appdetails")
+            .match_query(Matcher::UrlEncoded("appids".into(), "1".into()))
+            .with_body(r#"{"1":{"success":false}}"#)
+            .create();
+
+        assert!(matches!(client().get_app_details(1), Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn published_file_details() {
+        let _m = mock("POST", "/ISteamRemoteStorage/GetPublishedFileDetails/v1/")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("itemcount".into(), "2".into()),
+                Matcher::UrlEncoded("publishedfileids[0]".into(), "450814997".into()),
+                Matcher::UrlEncoded("publishedfileids[1]".into(), "1".into()),
+            ]))
+            .with_body(
+                r#"{"response":{"result":1,"resultcount":2,"publishedfiledetails":[
+                    {"publishedfileid":"450814997","result":1,"creator":"76561198024806434","consumer_app_id":107410,"file_size":"3145728","title":"CBA_A3","time_updated":1650000000,"tags":[{"tag":"Mod"}]},
+                    {"publishedfileid":"1","result":9}
+                ]}}"#,
+            )
+            .create();
+
+        let details = client()
+            .get_published_file_details(&[450814997, 1])
+            .unwrap();
+
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].publishedfileid, 450814997);
+        assert_eq!(details[0].title, "CBA_A3");
+        assert_eq!(details[0].creator, 76561198024806434);
+        assert_eq!(details[0].file_size, 3145728);
+        assert_eq!(details[0].tags[0].tag, "Mod");
+    }
+
+    #[test]
+    fn download() {
+        let _m = mock("GET", "/ugc/preview.jpg")
+            .with_body(&[0xff, 0xd8, 0xff][..])
+            .create();
+
+        let url = format!("{}/ugc/preview.jpg", mockito::server_url());
+
+        assert_eq!(client().download(&url).unwrap(), vec![0xff, 0xd8, 0xff]);
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let m = mock("GET", "/api/appdetails")
+            .match_query(Matcher::UrlEncoded("appids".into(), "2".into()))
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let result = client().get_app_details(2);
+
+        assert!(matches!(result, Err(Error::Status(s)) if s.as_u16() == 503));
+        m.assert();
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let m = mock("GET", "/api/appdetails")
+            .match_query(Matcher::UrlEncoded("appids".into(), "3".into()))
+            .with_status(403)
+            .expect(1)
+            .create();
+
+        assert!(matches!(client().get_app_details(3), Err(Error::Status(_))));
+        m.assert();
+    }
+
+    #[test]
+    fn does_not_retry_invalid_requests() {
+        let client = client().with_retries(2, Duration::from_secs(10));
+        let started = std::time::Instant::now();
+
+        let error = client.download("not a url").unwrap_err();
+
+        assert!(matches!(error, Error::Http(_)));
+        assert!(!error.is_transient());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
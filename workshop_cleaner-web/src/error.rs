@@ -0,0 +1,62 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Request could not be sent or the connection failed.
+    Http(reqwest::Error),
+    /// Server responded with a non-success status code.
+    Status(StatusCode),
+    /// Response body does not match the expected shape.
+    Decode(serde_json::Error),
+    /// Steam does not know the requested app or workshop item (or it is hidden).
+    NotFound,
+}
+
+impl Error {
+    /// Whether sending the same request again might succeed.
+    ///
+    /// Only timeouts, failed connections, rate limiting and server errors are, invalid requests
+    /// would fail again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Http(e) => e.is_timeout() || e.is_connect(),
+            Error::Status(s) => s.is_server_error() || *s == StatusCode::TOO_MANY_REQUESTS,
+            Error::Decode(_) | Error::NotFound => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "request failed: {}", e),
+            Error::Status(s) => write!(f, "unexpected response status: {}", s),
+            Error::Decode(e) => write!(f, "could not decode response: {}", e),
+            Error::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            Error::Decode(e) => Some(e),
+            Error::Status(_) | Error::NotFound => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Decode(e)
+    }
+}
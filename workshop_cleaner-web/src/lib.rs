@@ -0,0 +1,9 @@
+pub use client::SteamWebClient;
+pub use error::Error;
+pub use model::{AppDetails, PublishedFileDetails, PublishedFileTag};
+
+pub mod client;
+pub mod error;
+pub mod model;
+
+pub type Result<T> = std::result::Result<T, Error>;
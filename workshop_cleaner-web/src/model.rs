@@ -0,0 +1,78 @@
+use serde::{Deserialize, Deserializer};
+
+/// Store details of a Steam app, `data` part of the `appdetails` response.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AppDetails {
+    pub steam_appid: u32,
+    pub name: String,
+    #[serde(default)]
+    pub short_description: String,
+}
+
+/// Details of a single workshop item, as returned by `GetPublishedFileDetails`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct PublishedFileDetails {
+    #[serde(deserialize_with = "u64_from_str_or_number")]
+    pub publishedfileid: u64,
+    /// Steam `EResult` of the lookup, `1` means the item was found.
+    pub result: u32,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// SteamID64 of the author.
+    #[serde(default, deserialize_with = "u64_from_str_or_number")]
+    pub creator: u64,
+    #[serde(default)]
+    pub consumer_app_id: u32,
+    #[serde(default, deserialize_with = "u64_from_str_or_number")]
+    pub file_size: u64,
+    #[serde(default)]
+    pub preview_url: String,
+    #[serde(default)]
+    pub time_created: u64,
+    #[serde(default)]
+    pub time_updated: u64,
+    #[serde(default)]
+    pub tags: Vec<PublishedFileTag>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct PublishedFileTag {
+    pub tag: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AppDetailsEntry {
+    pub success: bool,
+    pub data: Option<AppDetails>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PublishedFileDetailsResponse {
+    pub response: PublishedFileDetailsList,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PublishedFileDetailsList {
+    #[serde(default)]
+    pub publishedfiledetails: Vec<PublishedFileDetails>,
+}
+
+/// Steam Web API is not consistent about 64 bit numbers, some endpoints return them as strings.
+fn u64_from_str_or_number<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StrOrNumber {
+        Str(String),
+        Number(u64),
+    }
+
+    match StrOrNumber::deserialize(deserializer)? {
+        StrOrNumber::Str(s) => s.parse().map_err(serde::de::Error::custom),
+        StrOrNumber::Number(n) => Ok(n),
+    }
+}